@@ -1,17 +1,97 @@
-use chess::{Board, MoveGen, Square, ChessMove};
+use chess::{Board, MoveGen, Square, ChessMove, Piece};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::Sender;
+use std::thread;
 mod negamax;
 mod alphabeta;
+pub mod limits;
+pub mod progress;
+pub mod score;
+pub mod see;
+pub mod stats;
 
-// Base function that generates the best move
-pub fn ai_move(board: &mut Board) -> ChessMove{
+use limits::SearchLimits;
+use progress::{SearchProgress, SearchResult};
+pub use alphabeta::{Bound, SearchConfig, Searcher, TranspositionTable};
+
+// Memory used by the transposition table
+const HASH_SIZE_MB: usize = 64;
+
+// chess::Board doesn't remember the moves that led to it, so the game
+// keeps track of every position reached to let the search spot repetitions
+#[derive(Clone)]
+pub struct GameHistory {
+    pub positions: Vec<u64>,    // Hashes of every position so far, including the current one
+    pub halfmove_clock: u32,    // Plies since the last capture or pawn move (for the 50 move rule)
+}
+
+impl GameHistory {
+    pub fn new(board: &Board) -> GameHistory {
+        GameHistory {
+            positions: vec![board.get_hash()],
+            halfmove_clock: 0,
+        }
+    }
+
+    // Record a move, call this before the move is made on the board
+    pub fn push(&mut self, board: &Board, m: ChessMove) {
+        if board.piece_on(m.get_source()) == Some(Piece::Pawn) || board.piece_on(m.get_dest()) != None {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        self.positions.push(board.make_move_new(m).get_hash());
+    }
+}
+
+// The searcher is kept for the whole game, so the engine can reuse what
+// it found while pondering and on its earlier moves
+pub fn new_searcher() -> Searcher {
+    let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    Searcher::new(SearchConfig {
+        hash_size_mb: HASH_SIZE_MB,
+        threads: threads,
+        ..SearchConfig::default()
+    })
+}
+
+// Base function that generates the best move. The search can be
+// cut short at any time by setting `stop` from another thread, and
+// reports how it is going through `progress` if a channel is given.
+// The whole result is returned so the caller can ponder on the expected reply
+pub fn ai_move(board: &mut Board, history: &mut GameHistory, limits: &SearchLimits, searcher: &mut Searcher,
+               stop: &Arc<AtomicBool>, progress: Option<Sender<SearchProgress>>) -> SearchResult{
     //let m = best_immediate(board);
     //let m = negamax::negamax_root(*board);
-    let m = alphabeta::alphabeta_root(*board, -std::i32::MAX, std::i32::MAX);
-    let mut result = Board::default();
-    board.make_move(m, &mut result);
-    *board = result;
+    let search = searcher.search(*board, history, limits, stop, progress);
+    if let Some(m) = search.best_move {
+        history.push(board, m);
+        let mut result = Board::default();
+        board.make_move(m, &mut result);
+        *board = result;
+    }
     
-    return m
+    return search
+}
+
+// Analyse the position without making a move: finds the best `multi_pv`
+// root moves, each with its own score and principal variation
+pub fn analyse(board: &Board, history: &GameHistory, limits: &SearchLimits, multi_pv: usize, searcher: &mut Searcher,
+               stop: &Arc<AtomicBool>, progress: Option<Sender<SearchProgress>>) -> SearchResult {
+    let previous = searcher.config().multi_pv;
+    searcher.set_multi_pv(multi_pv);
+    let result = searcher.search(*board, history, limits, stop, progress);
+    searcher.set_multi_pv(previous);
+    result
+}
+
+// Search a position from scratch with the given configuration, for
+// comparing search features against each other
+pub fn search_with_config(board: &Board, limits: &SearchLimits, config: &SearchConfig) -> SearchResult {
+    let history = GameHistory::new(board);
+    let stop = Arc::new(AtomicBool::new(false));
+    Searcher::new(config.clone()).search(*board, &history, limits, &stop, None)
 }
 
 // This function will return the best immediate move.
@@ -27,7 +107,8 @@ fn best_immediate(board: &mut Board) -> ChessMove{
 }
 
 // Iterates through all the possible captures and
-// returns the capture with the highest value
+// returns the one that wins the most material once
+// the exchange on that square is played out
 fn best_capture(board: &mut Board) -> Option<ChessMove>{
     //create an iterable
     let mut iterable = MoveGen::new_legal(&board);
@@ -41,7 +122,7 @@ fn best_capture(board: &mut Board) -> Option<ChessMove>{
     let mut dest: Square = Square::A2;
     let mut promotion = None;
     for take in &mut iterable {
-        let new_value = Some(capture_value(board, take.get_dest()).abs());
+        let new_value = Some(see::see(board, take) as f32);
         println!("{} to {}", take.get_source(), take.get_dest());
         if new_value > value || value == None{
             value = new_value;
@@ -59,29 +140,6 @@ fn best_capture(board: &mut Board) -> Option<ChessMove>{
     }
 }
 
-// Returns the value of the piece on a particular
-// square. (For future add heuristics)
-fn capture_value(board: &mut Board, square: Square) -> f32 {
-    let captured_piece = board.piece_on(square);
-    let mut value: f32;
-    match captured_piece {
-        Some(chess::Piece::Pawn)    => value = 10.0,
-        Some(chess::Piece::Knight)  => value = 30.0,
-        Some(chess::Piece::Bishop)  => value = 30.0,
-        Some(chess::Piece::Rook)    => value = 50.0,
-        Some(chess::Piece::Queen)   => value = 90.0,
-        Some(chess::Piece::King)    => value = 900.0,
-        None                        => value = 0.0
-    };
-
-    //Black pieces have negative values by convention
-    if board.color_on(square) == Some(chess::Color::Black) {
-        value = value * -1.0;
-    }
-
-    value   //Return
-}
-
 // Currently returns a random quiet move
 fn best_quiet(board: &mut Board) -> ChessMove {
     // create an iterable
@@ -1,32 +1,524 @@
-use chess::{Board, MoveGen, ChessMove};
+use chess::{Board, MoveGen, ChessMove, Piece, Rank, Square, EMPTY};
 use std::cmp;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant};
+use super::score::{self, Score, DRAW, INFINITY};
+use super::GameHistory;
+use super::limits::SearchLimits;
+use super::progress::{PvLine, SearchProgress, SearchResult};
+use super::see;
+use super::stats::SearchStats;
+mod eval;
+mod movepicker;
+mod ordering;
+mod pawns;
 mod pst;
+mod tt;
+
+use eval::evaluate;
+pub use eval::{piece_value, PIECES};
+use movepicker::MovePicker;
+use ordering::MoveOrdering;
+use pawns::PawnTable;
+pub use tt::{Bound, TranspositionTable};
+
+// Deepest ply the search can reach
+pub const MAX_PLY: usize = 128;
+
+// Maximum number of moves shown in the principal variation
+const MAX_PV_LENGTH: usize = 32;
+
+// How often the main thread sends progress while an iteration is running
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+// Safety margin for delta pruning in the quiescence search
+const DELTA_MARGIN: i32 = 200;
+
+// Null move pruning is only tried this far from the leaves, the search after the
+// null move is reduced by NULL_MOVE_REDUCTION plus one more ply for every 4 plies of depth
+const NULL_MOVE_MIN_DEPTH: u32 = 3;
+const NULL_MOVE_REDUCTION: u32 = 2;
+
+// Late move reductions only start after this many moves have been searched
+// at full depth, and only this far from the leaves
+const LMR_MIN_MOVES: usize = 3;
+const LMR_MIN_DEPTH: u32 = 3;
+
+// Half width of the aspiration window around the previous iteration's score
+const ASPIRATION_WINDOW: Score = 50;
+const ASPIRATION_MIN_DEPTH: u32 = 4;
+
+#[derive(Clone)]
+pub struct SearchConfig {
+   pub hash_size_mb: usize,   // Size of the transposition table
+   pub threads: usize,        // 1 keeps the search deterministic
+   pub multi_pv: usize,       // Number of best root moves to find
+
+   // Search one ply deeper after these moves
+   pub check_extension: bool,       // Moves that give check
+   pub recapture_extension: bool,   // Recapturing on the square the opponent just captured on
+   pub pawn_push_extension: bool,   // Pawns moving to the 7th rank
+
+   // Forward pruning, each kind is only tried this close to the leaves (0 turns it off).
+   // The margins are in centipawns for every ply of depth left
+   pub futility_max_depth: u32,
+   pub futility_margin: Score,
+   pub reverse_futility_max_depth: u32,
+   pub reverse_futility_margin: Score,
+   pub razoring_max_depth: u32,
+   pub razoring_margin: Score,
+}
 
-pub fn alphabeta_root(board: Board, mut alpha: i32, beta: i32) -> ChessMove {
-   let depth = 5;
-   let mut best_moves = Vec::new();
+impl Default for SearchConfig {
+   fn default() -> SearchConfig {
+      SearchConfig {
+         hash_size_mb: 16,
+         threads: 1,
+         multi_pv: 1,
+         check_extension: true,
+         recapture_extension: true,
+         pawn_push_extension: true,
+         futility_max_depth: 3,
+         futility_margin: 150,
+         reverse_futility_max_depth: 6,
+         reverse_futility_margin: 120,
+         razoring_max_depth: 2,
+         razoring_margin: 300,
+      }
+   }
+}
 
-   //create an iterable
-   let mut iterable = MoveGen::new_legal(&board);
+// The state of one search thread. Keeps track of the time and nodes used
+// so the search can be abandoned once a limit is reached
+struct Search {
+   id: usize,              // Thread number, 0 is the main thread
+   start: Instant,
+   time_limit: Option<Duration>,
+   ponder: Option<Arc<AtomicBool>>,   // The clock is stopped while this is set
+   max_nodes: Option<u64>,
+   nodes: u64,
+   total_nodes: Arc<AtomicU64>,  // Nodes searched by all threads, updated every 2048 nodes
+   stopped: bool,
+   stop: Arc<AtomicBool>,        // Set by the caller to abort the search
+   finished: Arc<AtomicBool>,    // Set by the main thread to stop the helpers
+   tt: Arc<TranspositionTable>,
+   ordering: MoveOrdering,
+   pawns: PawnTable,       // Each thread caches its own pawn structure scores
+   positions: Vec<u64>,    // Hashes of the game history followed by the current search path
+   reductions: [[u32; 64]; 64],   // Late move reductions by [depth][move number]
+   config: SearchConfig,
+   root_board: Board,
+   root_depth: u32,
+   seldepth: usize,
+   lines: Vec<PvLine>,     // Lines from the last completed iteration
+   lines_depth: u32,       // Depth of that iteration
+   capture_squares: [Option<Square>; MAX_PLY],   // Where the move made at each ply captured, for recaptures
+   progress: Option<Sender<SearchProgress>>,     // Only the main thread reports progress
+   last_report: Instant,
+   stats: SearchStats,
+}
+
+impl Search {
+   fn new(id: usize, shared: &SharedState, limits: &SearchLimits, history: &GameHistory, config: &SearchConfig,
+          ordering: MoveOrdering, pawns: PawnTable) -> Search {
+      Search {
+         id: id,
+         start: shared.start,
+         time_limit: limits.time_budget(),
+         ponder: limits.ponder.clone(),
+         max_nodes: limits.nodes,
+         nodes: 0,
+         total_nodes: shared.total_nodes.clone(),
+         stopped: false,
+         stop: shared.stop.clone(),
+         finished: shared.finished.clone(),
+         tt: shared.tt.clone(),
+         ordering: ordering,
+         pawns: pawns,
+         positions: history.positions.clone(),
+         reductions: reduction_table(),
+         config: config.clone(),
+         root_board: Board::default(),
+         root_depth: 0,
+         seldepth: 0,
+         lines: Vec::new(),
+         lines_depth: 0,
+         capture_squares: [None; MAX_PLY],
+         progress: None,
+         last_report: shared.start,
+         stats: SearchStats::default(),
+      }
+   }
+
+   // Instant::now() and the shared flags aren't free, so only look at them every 2048 nodes
+   fn check_time(&mut self) -> bool {
+      if self.nodes & 2047 == 0 {
+         self.total_nodes.fetch_add(2048, Ordering::Relaxed);
+         if self.stop.load(Ordering::Relaxed) || self.finished.load(Ordering::Relaxed) || self.out_of_time() {
+            self.stopped = true;
+         }
+         if self.progress.is_some() && self.last_report.elapsed() >= PROGRESS_INTERVAL {
+            self.report(false);
+         }
+      }
+      if let Some(max_nodes) = self.max_nodes {
+         if self.total_nodes() >= max_nodes {
+            self.stopped = true;
+         }
+      }
+      self.stopped
+   }
+
+   // Still waiting for the opponent to play the expected move? The clock
+   // starts from the moment the ponder hit is noticed
+   fn pondering(&mut self) -> bool {
+      let pondering = match self.ponder {
+         Some(ref ponder) => ponder.load(Ordering::Relaxed),
+         None => return false,
+      };
+      if !pondering {
+         self.start = Instant::now();
+         self.ponder = None;
+      }
+      pondering
+   }
+
+   fn out_of_time(&mut self) -> bool {
+      if self.pondering() {
+         return false
+      }
+      match self.time_limit {
+         Some(limit) => self.start.elapsed() >= limit,
+         None => false,
+      }
+   }
+
+   fn total_nodes(&self) -> u64 {
+      self.total_nodes.load(Ordering::Relaxed) + (self.nodes & 2047)
+   }
+
+   // Send the current state of the search to whoever is listening. Every line
+   // is sent for a completed iteration, only the best one while it is running
+   fn report(&mut self, completed: bool) {
+      self.last_report = Instant::now();
+      if let Some(ref sender) = self.progress {
+         let time = self.start.elapsed();
+         let nodes = self.total_nodes();
+         let millis = cmp::max(time.as_millis() as u64, 1);
+         let hashfull = self.tt.hashfull();
+         let count = if completed { self.lines.len() } else { cmp::min(self.lines.len(), 1) };
+
+         for (i, line) in self.lines[..count].iter().enumerate() {
+            let _ = sender.send(SearchProgress {
+               depth: self.lines_depth,
+               seldepth: self.seldepth as u32,
+               multipv: i as u32 + 1,
+               score: line.score,
+               nodes: nodes,
+               nps: nodes * 1000 / millis,
+               time: time,
+               hashfull: hashfull,
+               pv: line.pv.clone(),
+               completed: completed,
+            });
+         }
+      }
+   }
+
+   // Has the current position (last on the stack) already occurred since the last
+   // capture or pawn move? Only positions with the same side to move are checked
+   fn is_repetition(&self, halfmove: u32) -> bool {
+      let n = self.positions.len();
+      let hash = self.positions[n - 1];
+      let limit = cmp::min(halfmove as usize, n - 1);
+
+      let mut i = 2;
+      while i <= limit {
+         if self.positions[n - 1 - i] == hash { return true }
+         i += 2;
+      }
+      false
+   }
+}
+
+// Late moves are reduced more the deeper the search and the later the move
+fn reduction_table() -> [[u32; 64]; 64] {
+   let mut table = [[0; 64]; 64];
+   for depth in 1..64 {
+      for n in 1..64 {
+         table[depth][n] = (0.75 + (depth as f64).ln() * (n as f64).ln() / 2.25) as u32;
+      }
+   }
+   table
+}
+
+// The halfmove clock after making the move
+fn next_halfmove(board: &Board, m: ChessMove, halfmove: u32) -> u32 {
+   if board.piece_on(m.get_source()) == Some(Piece::Pawn) || ordering::is_capture(board, m) {
+      0
+   } else {
+      halfmove + 1
+   }
+}
+
+// Everything the search threads share
+struct SharedState {
+   start: Instant,
+   tt: Arc<TranspositionTable>,
+   total_nodes: Arc<AtomicU64>,
+   stop: Arc<AtomicBool>,
+   finished: Arc<AtomicBool>,
+}
+
+// Owns everything that is worth keeping from one search to the next during
+// a game: the transposition table and each thread's killer, history and pawn tables
+pub struct Searcher {
+   config: SearchConfig,
+   tt: Arc<TranspositionTable>,
+   orderings: Vec<MoveOrdering>,   // One for each thread, the main thread's first
+   pawn_tables: Vec<PawnTable>,    // Same order as the orderings
+   nodes: u64,                     // Nodes searched since the last new game
+}
+
+impl Searcher {
+   pub fn new(config: SearchConfig) -> Searcher {
+      let threads = cmp::max(config.threads, 1);
+      Searcher {
+         tt: Arc::new(TranspositionTable::new(config.hash_size_mb)),
+         orderings: (0..threads).map(|_| MoveOrdering::new()).collect(),
+         pawn_tables: (0..threads).map(|_| PawnTable::new()).collect(),
+         config: config,
+         nodes: 0,
+      }
+   }
+
+   // Forget everything learned from the previous game
+   pub fn new_game(&mut self) {
+      self.tt.clear();
+      for ordering in self.orderings.iter_mut() {
+         *ordering = MoveOrdering::new();
+      }
+      self.nodes = 0;
+   }
+
+   pub fn config(&self) -> &SearchConfig {
+      &self.config
+   }
+
+   pub fn set_multi_pv(&mut self, multi_pv: usize) {
+      self.config.multi_pv = multi_pv;
+   }
+
+   pub fn nodes(&self) -> u64 {
+      self.nodes
+   }
+
+   // Lazy SMP: every extra thread runs its own search of the same position and they
+   // help each other through the shared transposition table. The main thread's move
+   // is played once it is done. Setting `stop` ends the search early, the best move
+   // from the last completed iteration is still returned
+   pub fn search(&mut self, board: Board, history: &GameHistory, limits: &SearchLimits, stop: &Arc<AtomicBool>,
+                 progress: Option<Sender<SearchProgress>>) -> SearchResult {
+      let shared = SharedState {
+         start: Instant::now(),
+         tt: self.tt.clone(),
+         total_nodes: Arc::new(AtomicU64::new(0)),
+         stop: stop.clone(),
+         finished: Arc::new(AtomicBool::new(false)),
+      };
+      let max_depth = limits.max_depth();
+      let config = &self.config;
+
+      // Age what was kept from the previous searches
+      self.tt.new_search();
+      for ordering in self.orderings.iter_mut() {
+         ordering.new_search();
+      }
+
+      // Only search the root moves the caller asked for
+      let legal_moves: Vec<ChessMove> = MoveGen::new_legal(&board).collect();
+      let root_moves: Vec<ChessMove> = legal_moves.iter().cloned()
+         .filter(|m| limits.search_moves.is_empty() || limits.search_moves.contains(m))
+         .filter(|m| !limits.exclude_moves.contains(m))
+         .collect();
+
+      // Checkmate, stalemate or every move was left out, nothing to search
+      if root_moves.is_empty() {
+         let mated = legal_moves.is_empty() && *board.checkers() != EMPTY;
+         return SearchResult {
+            best_move: None,
+            score: if mated { score::mated_in(0) } else { DRAW },
+            lines: Vec::new(),
+            nodes: 0,
+            stats: SearchStats::default(),
+         }
+      }
 
-   // iterate over every move
-   let targets = board.color_combined(board.side_to_move());
-   iterable.set_iterator_mask(!*targets);
+      // Each thread takes its tables for the search and hands them back at the end
+      let mut tables = self.orderings.drain(..).zip(self.pawn_tables.drain(..));
+      let (main_ordering, main_pawns) = tables.next().unwrap();
+
+      let mut helpers = Vec::new();
+      for (id, (ordering, pawns)) in tables.enumerate() {
+         let mut helper = Search::new(id + 1, &shared, limits, history, config, ordering, pawns);
+         let halfmove = history.halfmove_clock;
+         let moves = root_moves.clone();
+         helpers.push(thread::spawn(move || {
+            iterative_deepening(board, moves, max_depth, halfmove, 1, &mut helper);
+            (helper.ordering, helper.pawns, helper.stats)
+         }));
+      }
+
+      let mut search = Search::new(0, &shared, limits, history, config, main_ordering, main_pawns);
+      search.progress = progress;
+      let lines = iterative_deepening(board, root_moves, max_depth, history.halfmove_clock, config.multi_pv, &mut search);
+
+      // A move can't be played before the opponent has made theirs, so a ponder
+      // search that runs out of depth waits for the ponder hit or to be stopped
+      while search.pondering() && !stop.load(Ordering::Relaxed) {
+         thread::sleep(Duration::from_millis(1));
+      }
+
+      shared.finished.store(true, Ordering::Relaxed);
+      let nodes = search.total_nodes();
+      let mut stats = search.stats;
+      stats.nodes = nodes;
+      stats.time = search.start.elapsed();
+      self.orderings.push(search.ordering);
+      self.pawn_tables.push(search.pawns);
+      for helper in helpers {
+         let (ordering, pawns, helper_stats) = helper.join().unwrap();
+         self.orderings.push(ordering);
+         self.pawn_tables.push(pawns);
+         stats.merge(&helper_stats);
+      }
+      self.nodes += nodes;
+
+      SearchResult {
+         best_move: Some(lines[0].pv[0]),
+         score: lines[0].score,
+         lines: lines,
+         nodes: nodes,
+         stats: stats,
+      }
+   }
+}
+
+// Iterative deepening: search to depth 1, 2, 3... until max_depth or
+// one of the limits is reached and return the lines found by the last
+// iteration that was completed
+fn iterative_deepening(board: Board, root_moves: Vec<ChessMove>, max_depth: u32, halfmove: u32, multi_pv: usize, search: &mut Search) -> Vec<PvLine> {
+   let mut root_moves = root_moves;
+   let mut lines = vec![PvLine { score: 0, pv: vec![root_moves[0]] }];
+   let multi_pv = cmp::max(cmp::min(multi_pv, root_moves.len()), 1);
+   search.root_board = board;
+
+   'deepening: for iteration in 1..=max_depth {
+      // Half of the helper threads search one ply deeper than the others so the
+      // threads don't all work on exactly the same thing
+      let depth = cmp::min(iteration + (search.id % 2) as u32, max_depth);
+      search.root_depth = depth;
+      search.seldepth = 0;
+      let iteration_nodes = search.nodes;
+
+      // MultiPV: find the best move, then the best of the moves that are left and so on
+      let mut new_lines: Vec<PvLine> = Vec::new();
+      for pv_index in 0..multi_pv {
+         let candidates: Vec<ChessMove> = root_moves.iter().cloned()
+            .filter(|m| !new_lines.iter().any(|line| line.pv[0] == *m))
+            .collect();
+
+         // Aspiration windows: expect the score to be close to the last iteration's,
+         // and widen the window whenever the search falls outside of it
+         let mut delta = ASPIRATION_WINDOW;
+         let mut alpha = -INFINITY;
+         let mut beta = INFINITY;
+         if let Some(prev) = lines.get(pv_index) {
+            if depth >= ASPIRATION_MIN_DEPTH && !score::is_mate(prev.score) {
+               alpha = prev.score - delta;
+               beta = prev.score + delta;
+            }
+         }
+
+         let (value, m) = loop {
+            let (value, m) = search_root(board, &candidates, depth, alpha, beta, halfmove, search);
+            if search.stopped { break (value, m) }
+
+            if value <= alpha {
+               alpha = cmp::max(value - delta, -INFINITY);
+            } else if value >= beta {
+               beta = cmp::min(value + delta, INFINITY);
+            } else {
+               break (value, m)
+            }
+            delta *= 2;
+         };
+
+         // A partially searched iteration can't be trusted, keep the previous lines
+         if search.stopped { break 'deepening }
+
+         let mut pv = vec![m];
+         pv.extend(search.tt.get_pv(board.make_move_new(m), MAX_PV_LENGTH - 1));
+         new_lines.push(PvLine { score: value, pv: pv });
+      }
+
+      new_lines.sort_by(|a, b| b.score.cmp(&a.score));
+      lines = new_lines;
+      search.lines = lines.clone();
+      search.lines_depth = depth;
+      search.stats.nodes_per_depth.push(search.nodes - iteration_nodes);
+      search.tt.store(board.get_hash(), Some(lines[0].pv[0]), lines[0].score, depth, Bound::Exact);
+      search.report(true);
+
+      // Search the moves in the order of the lines in the next iteration
+      let mut ordered: Vec<ChessMove> = lines.iter().map(|line| line.pv[0]).collect();
+      ordered.extend(root_moves.iter().cloned().filter(|m| !lines.iter().any(|line| line.pv[0] == *m)));
+      root_moves = ordered;
+
+      if search.out_of_time() { break }
+   }
+
+   return lines
+}
+
+// Search every root move within the window, returns the best score and move
+fn search_root(board: Board, root_moves: &[ChessMove], depth: u32, mut alpha: Score, beta: Score, halfmove: u32, search: &mut Search) -> (Score, ChessMove) {
+   let mut best_value = -INFINITY;
+   let mut best_move = root_moves[0];
 
-   for chessmove in iterable {
+   for (i, &chessmove) in root_moves.iter().enumerate() {
       let board_copy = board.make_move_new(chessmove);
-      let value = -alphabeta(board_copy, depth - 1, -beta, -alpha, true);
-      if value > alpha { 
-         alpha = value;
-         best_moves.clear();
-         best_moves.push(chessmove);
+      let color = board_copy.side_to_move() == chess::Color::White;
+      let child_halfmove = next_halfmove(&board, chessmove, halfmove);
+      search.positions.push(board_copy.get_hash());
+      search.capture_squares[0] = capture_square(&board, chessmove);
+
+      // Principal variation search: the first move gets the full window, the
+      // rest only have to prove they are worse and are re-searched if they aren't
+      let mut value;
+      if i == 0 {
+         value = -alphabeta(board_copy, depth - 1, -beta, -alpha, color, 1, child_halfmove, true, search);
+      } else {
+         value = -alphabeta(board_copy, depth - 1, -alpha - 1, -alpha, color, 1, child_halfmove, true, search);
+         if value > alpha && value < beta {
+            value = -alphabeta(board_copy, depth - 1, -beta, -alpha, color, 1, child_halfmove, true, search);
+         }
       }
+      search.positions.pop();
+
+      if search.stopped { break }
+      if value > best_value {
+         best_value = value;
+         best_move = chessmove;
+      }
+      alpha = cmp::max(alpha, value);
+      if alpha >= beta { break }
    };
 
-   // Get the best move
-   let best = best_moves[0];
-   return best
-   
+   (best_value, best_move)
 }
 
 /* int alphaBeta( int alpha, int beta, int depthleft ) {
@@ -40,118 +532,291 @@ pub fn alphabeta_root(board: Board, mut alpha: i32, beta: i32) -> ChessMove {
     }
     return alpha;
  } */
-fn alphabeta(board: Board, depth: u32, mut alpha: i32, beta: i32, color: bool) -> i32{
-   //If the board status is checkmate, the current player has lost -> return large negative number
-   if board.status() == chess::BoardStatus::Checkmate { return -100000 }
-   else if board.status() == chess::BoardStatus::Stalemate { return 0 }
+fn alphabeta(board: Board, depth: u32, mut alpha: Score, beta: Score, color: bool, ply: usize, halfmove: u32, allow_null: bool, search: &mut Search) -> Score{
+   search.nodes += 1;
+   search.seldepth = cmp::max(search.seldepth, ply);
+   if search.check_time() { return 0 }
+
+   // Draw by repetition or the 50 move rule. Checkmate on the 100th halfmove still
+   // counts as a win, generating the moves is only needed when the king is in check
+   if halfmove >= 100 {
+      let mated = *board.checkers() != EMPTY && MoveGen::new_legal(&board).len() == 0;
+      return if mated { score::mated_in(ply) } else { DRAW }
+   }
+   if search.is_repetition(halfmove) { return DRAW }
+
+   // Mate distance pruning: if a shorter mate has already been found elsewhere
+   // there is no point looking for a longer one here
+   alpha = cmp::max(alpha, score::mated_in(ply));
+   let beta = cmp::min(beta, score::mate_in(ply + 1));
+   if alpha >= beta { return alpha }
+
+   // See if this position has already been searched deep enough
+   let hash = board.get_hash();
+   let mut hash_move = None;
+   search.stats.tt_probes += 1;
+   if let Some(entry) = search.tt.probe(hash) {
+      search.stats.tt_hits += 1;
+      hash_move = entry.best_move;
+      if entry.depth as u32 >= depth {
+         let tt_score = score::from_tt(entry.score, ply);
+         let cutoff = match entry.bound {
+            Bound::Exact => true,
+            Bound::Lower => tt_score >= beta,
+            Bound::Upper => tt_score <= alpha,
+         };
+         if cutoff {
+            search.stats.tt_cutoffs += 1;
+            return tt_score
+         }
+      }
+   }
 
-   let color_modifier = if color {1} else {-1};
+    if depth == 0 || ply >= MAX_PLY { return quiesce(board, alpha, beta, color, ply, search) }
+
+    let in_check = *board.checkers() != EMPTY;
+    let color_modifier = if color {1} else {-1};
+    let static_eval = evaluate(&board, &mut search.pawns) * color_modifier;
+    let pv_node = beta - alpha > 1;
+
+    // None of the forward pruning is safe in check or once mate scores are involved,
+    // and it is kept out of PV nodes so the principal variation is searched properly
+    let can_prune = !pv_node && !in_check && !score::is_mate(alpha) && !score::is_mate(beta);
+
+    // Reverse futility pruning (static null move): the static eval is so far above beta
+    // that even after giving back a margin the move would still fail high
+    if can_prune && depth <= search.config.reverse_futility_max_depth
+       && static_eval - search.config.reverse_futility_margin * (depth as Score) >= beta {
+       search.stats.reverse_futility_prunes += 1;
+       return static_eval
+    }
 
-    if depth == 0 { return evaluate(board) * color_modifier }
+    // Razoring: the static eval is so far below alpha that only winning material could
+    // help, if the quiescence search can't find any the position is given up on
+    if can_prune && depth <= search.config.razoring_max_depth
+       && static_eval + search.config.razoring_margin * (depth as Score) < alpha {
+       let value = quiesce(board, alpha, alpha + 1, color, ply, search);
+       if search.stopped { return 0 }
+       if value <= alpha {
+          search.stats.razoring_prunes += 1;
+          return value
+       }
+    }
 
-    let mut value = -std::i32::MAX;
+    // Futility pruning: close to the leaves quiet moves can't bring a position this far
+    // below alpha back up, so they are skipped after the first move
+    let futile = can_prune && depth <= search.config.futility_max_depth
+       && static_eval + search.config.futility_margin * (depth as Score) <= alpha;
+
+    // Null move pruning: let the opponent move twice in a row, if a reduced search still
+    // fails high the position is good enough that the full search would too. Not done
+    // in check (illegal) or when only pawns are left, where zugzwang is likely
+    if allow_null && !in_check && depth >= NULL_MOVE_MIN_DEPTH && beta < score::MATE_BOUND
+       && has_non_pawn_material(&board) && static_eval >= beta {
+       if let Some(null_board) = board.null_move() {
+          let reduction = NULL_MOVE_REDUCTION + depth / 4;
+          search.positions.push(null_board.get_hash());
+          search.capture_squares[ply] = None;
+          let score = -alphabeta(null_board, depth.saturating_sub(1 + reduction), -beta, -beta + 1, !color, ply + 1, 0, false, search);
+          search.positions.pop();
+
+          if search.stopped { return 0 }
+          if score >= beta {
+             // A mate found after a null move isn't real
+             search.stats.null_move_prunes += 1;
+             return if score::is_mate(score) { beta } else { score }
+          }
+       }
+    }
 
-    //create an iterable
-    let mut iterable = MoveGen::new_legal(&board);
+    let alpha_orig = alpha;
+    let mut value = -INFINITY;
+    let mut best_move = None;
 
-    // iterate over every move
-    let targets = board.color_combined(board.side_to_move());
-    iterable.set_iterator_mask(!*targets);
+    // Moves are generated in stages as they are needed, the hash move is
+    // tried before generating anything in case it causes a cutoff
+    let mut picker = MovePicker::new(&board, hash_move, &search.ordering, ply);
+    let mut move_count = 0;
 
-    for chessmove in iterable {
+    while let Some(chessmove) = picker.next(&search.ordering) {
+        let i = move_count;
+        move_count += 1;
         let board_copy = board.make_move_new(chessmove);
-        value = cmp::max(-alphabeta(board_copy, depth - 1, -beta, -alpha, !color), value);
+        let child_halfmove = next_halfmove(&board, chessmove, halfmove);
+        let is_capture = ordering::is_capture(&board, chessmove);
+        let is_quiet = !is_capture && chessmove.get_promotion() == None;
+        let gives_check = *board_copy.checkers() != EMPTY;
+        if futile && i > 0 && is_quiet && !gives_check {
+           search.stats.futility_prunes += 1;
+           continue
+        }
+        search.positions.push(board_copy.get_hash());
+        search.capture_squares[ply] = capture_square(&board, chessmove);
+
+        // Extensions: forcing moves get searched one ply deeper. They stop once the
+        // path is twice as long as the iteration depth so checks can't go on forever
+        let mut extension = 0;
+        if ply < 2 * search.root_depth as usize {
+           let is_recapture = is_capture && search.capture_squares[ply - 1] == Some(chessmove.get_dest());
+           if search.config.check_extension && gives_check {
+              extension = 1;
+           } else if search.config.recapture_extension && is_recapture {
+              extension = 1;
+           } else if search.config.pawn_push_extension && is_pawn_push_to_seventh(&board, chessmove) {
+              extension = 1;
+           }
+        }
+        let new_depth = depth - 1 + extension;
+
+        // Late move reductions: quiet moves this far down the list are unlikely to be
+        // any good, so search them shallower and only go full depth if they beat alpha
+        let mut reduction = 0;
+        if i >= LMR_MIN_MOVES && depth >= LMR_MIN_DEPTH && is_quiet && !in_check && extension == 0
+           && !gives_check && !search.ordering.is_killer(chessmove, ply) {
+           reduction = search.reductions[cmp::min(depth as usize, 63)][cmp::min(i, 63)];
+           reduction = cmp::min(reduction, depth - 2);
+           if reduction > 0 { search.stats.reductions += 1 }
+        }
+
+        // Principal variation search: assume the first move is the best and search the
+        // others with a null window, re-searching at full depth and window if they beat alpha
+        let mut score;
+        if i == 0 {
+           score = -alphabeta(board_copy, new_depth, -beta, -alpha, !color, ply + 1, child_halfmove, true, search);
+        } else {
+           score = -alphabeta(board_copy, new_depth - reduction, -alpha - 1, -alpha, !color, ply + 1, child_halfmove, true, search);
+           if score > alpha && reduction > 0 {
+              search.stats.reduction_researches += 1;
+              score = -alphabeta(board_copy, new_depth, -alpha - 1, -alpha, !color, ply + 1, child_halfmove, true, search);
+           }
+           if score > alpha && score < beta {
+              score = -alphabeta(board_copy, new_depth, -beta, -alpha, !color, ply + 1, child_halfmove, true, search);
+           }
+        }
+        search.positions.pop();
+        if score > value {
+           value = score;
+           best_move = Some(chessmove);
+        }
         alpha = cmp::max(alpha, value);
-        if alpha >= beta {break}
+        if alpha >= beta {
+           search.stats.beta_cutoffs += 1;
+           if i == 0 { search.stats.first_move_cutoffs += 1 }
+
+           // Remember quiet moves that cause cutoffs for the killer and history heuristics
+           if is_quiet {
+              search.ordering.update(&board, chessmove, depth, ply);
+           }
+           break
+        }
     };
-    return value
-}
 
-fn evaluate(board: Board) -> i32 {
+    // Results of an aborted search are garbage, don't let them into the table
+    if search.stopped { return 0 }
 
-   let mut value = 0;
-   value += get_piece_balance(board, chess::Piece::Pawn);
-   value += get_piece_balance(board, chess::Piece::Bishop);
-   value += get_piece_balance(board, chess::Piece::Knight);
-   value += get_piece_balance(board, chess::Piece::Rook);
-   value += get_piece_balance(board, chess::Piece::Queen);
-   value += get_piece_balance(board, chess::Piece::King);
+    // No legal moves: checkmate, the current player has lost -> the sooner the worse, or stalemate
+    if move_count == 0 {
+       return if in_check { score::mated_in(ply) } else { DRAW }
+    }
+
+    let bound = if value <= alpha_orig {
+       Bound::Upper
+    } else if value >= beta {
+       Bound::Lower
+    } else {
+       Bound::Exact
+    };
+    search.tt.store(hash, best_move, score::to_tt(value, ply), depth, bound);
 
-   value
+    return value
 }
 
-fn get_piece_balance(board: Board, piece: chess::Piece) -> i32 {
-   let mut color = board.color_combined(chess::Color::White);
-   let pieces = board.pieces(piece);
-   let mut value = 0;
-   let mut position_value = 0;
+// The square a capture was made on, en passant counts as capturing on the destination
+fn capture_square(board: &Board, m: ChessMove) -> Option<Square> {
+   if ordering::is_capture(board, m) { Some(m.get_dest()) } else { None }
+}
 
-   let cost = match piece {
-       chess::Piece::Pawn => 100,
-       chess::Piece::Bishop => 330,
-       chess::Piece::Knight => 320,
-       chess::Piece::Rook => 500,
-       chess::Piece::Queen => 900,
-       chess::Piece::King => 20000,
+fn is_pawn_push_to_seventh(board: &Board, m: ChessMove) -> bool {
+   let seventh = match board.side_to_move() {
+      chess::Color::White => Rank::Seventh,
+      chess::Color::Black => Rank::Second,
    };
+   board.piece_on(m.get_source()) == Some(Piece::Pawn) && m.get_dest().get_rank() == seventh
+}
 
-   let mut pst_cost = match piece {
-      chess::Piece::Pawn => pst::PST_WHITE_PAWN,
-      chess::Piece::Bishop => pst::PST_WHITE_BISHOP,
-      chess::Piece::Knight => pst::PST_WHITE_KNIGHT,
-      chess::Piece::Rook => pst::PST_WHITE_ROOK,
-      chess::Piece::Queen => pst::PST_WHITE_QUEEN,
-      chess::Piece::King => pst::PST_WHITE_KING,
-  };
-
-   //Get the binary representation of all {color} {pieces}
-   let n = (*pieces & *color).to_size(0);
-
-   let mut num = n.count_ones() as i32; // Get the number of 1's in the binary number (and cast from u32 to i32)
+// Does the side to move have anything other than pawns and the king?
+fn has_non_pawn_material(board: &Board) -> bool {
+   let pieces = *board.pieces(Piece::Knight) | *board.pieces(Piece::Bishop)
+              | *board.pieces(Piece::Rook) | *board.pieces(Piece::Queen);
+   (pieces & *board.color_combined(board.side_to_move())) != EMPTY
+}
 
-   value += num * cost;
+// Quiescence search: keep searching captures and promotions until the position
+// is quiet, so the static evaluation is never taken in the middle of an exchange
+fn quiesce(board: Board, mut alpha: Score, beta: Score, color: bool, ply: usize, search: &mut Search) -> Score {
+   search.nodes += 1;
+   search.stats.qnodes += 1;
+   search.seldepth = cmp::max(search.seldepth, ply);
+   if search.check_time() { return 0 }
 
-   //Now use Piece Square Tables to check the value of the location of each piece
-   let mut n2 = n;
+   let mut iterable = MoveGen::new_legal(&board);
+   let in_check = *board.checkers() != EMPTY;
 
-   while n2 != 0 {
-      let square = n2.trailing_zeros();   //The number of trailing 0s will give the index of the first 1 (LSB = A1)
-      n2 &= n2 - 1;                       //ANDing with (itself - 1) will remove the least significant 1
-      position_value = position_value + pst_cost[square as usize];
+   if iterable.len() == 0 {
+      return if in_check { score::mated_in(ply) } else { DRAW }
    }
 
-   value = value + position_value;
-
-   // Check black pieces
-   position_value = 0;
-
-   let mut pst_cost = match piece {
-      chess::Piece::Pawn => pst::PST_BLACK_PAWN,
-      chess::Piece::Bishop => pst::PST_BLACK_BISHOP,
-      chess::Piece::Knight => pst::PST_BLACK_KNIGHT,
-      chess::Piece::Rook => pst::PST_BLACK_ROOK,
-      chess::Piece::Queen => pst::PST_BLACK_QUEEN,
-      chess::Piece::King => pst::PST_BLACK_KING,
-  };
-
-   color = board.color_combined(chess::Color::Black);
-
-   //Get the binary representation of all {color} {pieces}
-   let n = (*pieces & *color).to_size(0);
-
-   num = n.count_ones() as i32; // Get the number of 1's in the binary number (and cast from u32 to i32)
-
-   value -= num * cost;
-
-   let mut n2 = n;
-
-   while n2 != 0 {
-      let square = n2.trailing_zeros();   //The number of trailing 0s will give the index of the first 1 (LSB = A1)
-      n2 &= n2 - 1;  // ANDing with (itself - 1) will remove the least significant 1
-      position_value = position_value + pst_cost[square as usize];
+   let color_modifier = if color {1} else {-1};
+   if ply >= MAX_PLY { return evaluate(&board, &mut search.pawns) * color_modifier }
+
+   let mut value = -INFINITY;
+   let mut stand_pat = None;
+
+   // When in check every evasion has to be searched, otherwise only captures and promotions
+   if !in_check {
+      // Stand pat: the side to move doesn't have to capture anything
+      let eval = evaluate(&board, &mut search.pawns) * color_modifier;
+      if eval >= beta { return eval }
+      alpha = cmp::max(alpha, eval);
+      value = eval;
+      stand_pat = Some(eval);
+
+      let targets = board.color_combined(!board.side_to_move());
+      let promotion_squares = chess::get_rank(Rank::First) | chess::get_rank(Rank::Eighth);
+      iterable.set_iterator_mask(*targets | promotion_squares);
    }
 
-   value = value - position_value;
+   let mut moves: Vec<ChessMove> = iterable.collect();
+   search.ordering.order_moves(&board, &mut moves, None, ply);
+
+   for chessmove in moves {
+      if let Some(eval) = stand_pat {
+         let captured = board.piece_on(chessmove.get_dest());
+         if captured == None && chessmove.get_promotion() == None { continue }
+
+         // Delta pruning: skip captures that can't raise the score to alpha even with a margin
+         if chessmove.get_promotion() == None {
+            if let Some(piece) = captured {
+               if eval + piece_value(piece) + DELTA_MARGIN <= alpha {
+                  search.stats.delta_prunes += 1;
+                  continue
+               }
+            }
+         }
+
+         // Captures that lose material once the exchange is played out are never worth it here
+         if see::see(&board, chessmove) < 0 {
+            search.stats.see_prunes += 1;
+            continue
+         }
+      }
+
+      let board_copy = board.make_move_new(chessmove);
+      let score = -quiesce(board_copy, -beta, -alpha, !color, ply + 1, search);
+      value = cmp::max(value, score);
+      alpha = cmp::max(alpha, value);
+      if alpha >= beta {break}
+   };
 
    return value
-}
\ No newline at end of file
+}
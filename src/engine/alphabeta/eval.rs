@@ -0,0 +1,298 @@
+use chess::{BitBoard, Board, Color, Piece, Square, EMPTY};
+use std::cmp;
+use std::ops::{AddAssign, Mul, SubAssign};
+use super::pawns::{self, PawnTable};
+use super::pst;
+use super::super::score::Score;
+
+pub const PIECES: [Piece; 6] = [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen, Piece::King];
+
+// How much each piece counts towards the game phase, indexed by Piece::to_index().
+// All of the pieces on the board add up to MAX_PHASE, only kings and pawns is 0
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+const MAX_PHASE: i32 = 24;
+
+// Mobility: bonus for every safe square a piece can move to beyond the number it
+// usually has, so a piece with fewer than usual is penalised. Indexed by Piece::to_index()
+const MOBILITY_WEIGHTS: [Tapered; 6] = [
+    Tapered::new(0, 0), Tapered::new(4, 4), Tapered::new(5, 5),
+    Tapered::new(2, 4), Tapered::new(1, 2), Tapered::new(0, 0),
+];
+const MOBILITY_BASELINE: [i32; 6] = [0, 4, 7, 7, 14, 0];
+
+// King safety, only counted in the middlegame. The shield and storm scores are
+// indexed by how many ranks in front of the king the closest pawn on a file is
+const PAWN_SHIELD: [Score; 8] = [0, 15, 8, 2, 0, 0, 0, 0];
+const SHIELD_MISSING: Score = -15;
+const PAWN_STORM: [Score; 8] = [0, -5, -25, -15, -8, 0, 0, 0];
+const OPEN_FILE_NEAR_KING: Score = -25;         // No pawns on the file at all
+const SEMI_OPEN_FILE_NEAR_KING: Score = -12;    // Only enemy pawns on the file
+
+// Every enemy piece attacking the squares around the king adds its weight, but
+// the total only counts fully once several pieces join in: a lone attacker is no
+// danger. ATTACK_SCALE is the percentage counted by the number of attackers
+const ATTACKER_WEIGHTS: [Score; 6] = [0, 20, 20, 40, 80, 0];
+const ATTACK_SCALE: [Score; 8] = [0, 0, 50, 75, 88, 94, 97, 99];
+
+// Piece placement
+const BISHOP_PAIR: Tapered = Tapered::new(30, 50);
+const ROOK_OPEN_FILE: Tapered = Tapered::new(25, 10);
+const ROOK_SEMI_OPEN_FILE: Tapered = Tapered::new(12, 6);     // No friendly pawns on the file
+const QUEEN_OPEN_FILE: Tapered = Tapered::new(6, 4);
+const QUEEN_SEMI_OPEN_FILE: Tapered = Tapered::new(3, 2);
+const ROOK_ON_SEVENTH: Tapered = Tapered::new(15, 25);       // While it traps the king or attacks pawns there
+const KNIGHT_OUTPOST: Tapered = Tapered::new(20, 10);
+const BISHOP_OUTPOST: Tapered = Tapered::new(10, 5);
+const BAD_BISHOP: Tapered = Tapered::new(-3, -5);            // For every friendly pawn on the bishop's colour
+
+const LIGHT_SQUARES: u64 = 0x55AA_55AA_55AA_55AA;
+
+// A middlegame and an endgame score for the same term, blended together
+// by the game phase once everything has been added up
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Tapered {
+    pub mg: Score,
+    pub eg: Score,
+}
+
+impl Tapered {
+    pub const fn new(mg: Score, eg: Score) -> Tapered {
+        Tapered { mg: mg, eg: eg }
+    }
+}
+
+impl AddAssign for Tapered {
+    fn add_assign(&mut self, other: Tapered) {
+        self.mg += other.mg;
+        self.eg += other.eg;
+    }
+}
+
+impl SubAssign for Tapered {
+    fn sub_assign(&mut self, other: Tapered) {
+        self.mg -= other.mg;
+        self.eg -= other.eg;
+    }
+}
+
+impl Mul<i32> for Tapered {
+    type Output = Tapered;
+    fn mul(self, n: i32) -> Tapered {
+        Tapered::new(self.mg * n, self.eg * n)
+    }
+}
+
+// Static evaluation of the position from White's point of view
+pub fn evaluate(board: &Board, pawns: &mut PawnTable) -> Score {
+    let mut score = Tapered::default();
+    for &piece in PIECES.iter() {
+        score += piece_balance(board, piece, Color::White);
+        score -= piece_balance(board, piece, Color::Black);
+    }
+    score += pawns.evaluate(board);
+    score += mobility(board, Color::White);
+    score -= mobility(board, Color::Black);
+    score += king_safety(board, Color::White);
+    score -= king_safety(board, Color::Black);
+    score += placement(board, Color::White);
+    score -= placement(board, Color::Black);
+
+    taper(score, game_phase(board))
+}
+
+// MAX_PHASE at the start of the game down to 0 once only kings and pawns are left
+pub fn game_phase(board: &Board) -> i32 {
+    let phase: i32 = PIECES.iter()
+        .map(|&piece| board.pieces(piece).popcnt() as i32 * PHASE_WEIGHTS[piece.to_index()])
+        .sum();
+    cmp::min(phase, MAX_PHASE)
+}
+
+fn taper(score: Tapered, phase: i32) -> Score {
+    (score.mg * phase + score.eg * (MAX_PHASE - phase)) / MAX_PHASE
+}
+
+// Material and piece-square table score of all of one side's pieces of a kind
+fn piece_balance(board: &Board, piece: Piece, color: Color) -> Tapered {
+    let (mg_table, eg_table) = piece_square_tables(piece, color);
+    let pieces = *board.pieces(piece) & *board.color_combined(color);
+
+    let material = pieces.popcnt() as Score * piece_value(piece);
+    let mut score = Tapered::new(material, material);
+    for square in pieces {
+        score.mg += mg_table[square.to_index()];
+        score.eg += eg_table[square.to_index()];
+    }
+    score
+}
+
+// Counts the squares each knight, bishop, rook and queen can move to that aren't
+// occupied by a friendly piece or attacked by an enemy pawn
+fn mobility(board: &Board, color: Color) -> Tapered {
+    let occupied = *board.combined();
+    let enemy_pawns = *board.pieces(Piece::Pawn) & *board.color_combined(!color);
+    let attacked_by_pawns = BitBoard::new(pawns::pawn_attacks(enemy_pawns.0, !color));
+    let safe = !*board.color_combined(color) & !attacked_by_pawns;
+
+    let mut score = Tapered::default();
+    for &piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen].iter() {
+        for square in *board.pieces(piece) & *board.color_combined(color) {
+            let count = (piece_attacks(piece, square, occupied) & safe).popcnt() as i32;
+            score += MOBILITY_WEIGHTS[piece.to_index()] * (count - MOBILITY_BASELINE[piece.to_index()]);
+        }
+    }
+    score
+}
+
+// Pawn shield, pawn storms and open files in front of the king, and the enemy
+// pieces attacking the squares around it
+fn king_safety(board: &Board, color: Color) -> Tapered {
+    let king = board.king_square(color);
+    let file = king.get_file().to_index();
+    let rank = king.get_rank().to_index();
+    let own_pawns = (*board.pieces(Piece::Pawn) & *board.color_combined(color)).0;
+    let enemy_pawns = (*board.pieces(Piece::Pawn) & *board.color_combined(!color)).0;
+    let ahead = pawns::ranks_ahead(rank, color);
+    let mut safety = 0;
+
+    for f in file.saturating_sub(1)..=cmp::min(file + 1, 7) {
+        let file_mask = pawns::file_mask(f);
+
+        safety += match closest_pawn(own_pawns & file_mask & ahead, color) {
+            Some(pawn_rank) => PAWN_SHIELD[distance(pawn_rank, rank)],
+            None => SHIELD_MISSING,
+        };
+        if let Some(pawn_rank) = closest_pawn(enemy_pawns & file_mask & ahead, color) {
+            safety += PAWN_STORM[distance(pawn_rank, rank)];
+        }
+        if own_pawns & file_mask == 0 {
+            safety += if enemy_pawns & file_mask == 0 { OPEN_FILE_NEAR_KING } else { SEMI_OPEN_FILE_NEAR_KING };
+        }
+    }
+
+    let zone = chess::get_king_moves(king) | BitBoard::from_square(king);
+    let occupied = *board.combined();
+    let mut attackers = 0;
+    let mut attack_weight = 0;
+    for &piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen].iter() {
+        for square in *board.pieces(piece) & *board.color_combined(!color) {
+            if piece_attacks(piece, square, occupied) & zone != EMPTY {
+                attackers += 1;
+                attack_weight += ATTACKER_WEIGHTS[piece.to_index()];
+            }
+        }
+    }
+    safety -= attack_weight * ATTACK_SCALE[cmp::min(attackers, 7)] / 100;
+
+    Tapered::new(safety, 0)
+}
+
+// Bishop pair, rooks and queens on open files, rooks on the 7th,
+// outposts and bishops hemmed in by their own pawns
+fn placement(board: &Board, color: Color) -> Tapered {
+    let own = *board.color_combined(color);
+    let own_pawns = (*board.pieces(Piece::Pawn) & own).0;
+    let enemy_pawns = (*board.pieces(Piece::Pawn) & *board.color_combined(!color)).0;
+    let pawn_defended = pawns::pawn_attacks(own_pawns, color);
+    let mut score = Tapered::default();
+
+    let bishops = *board.pieces(Piece::Bishop) & own;
+    if bishops.popcnt() >= 2 {
+        score += BISHOP_PAIR;
+    }
+
+    for &(piece, open, semi_open) in [(Piece::Rook, ROOK_OPEN_FILE, ROOK_SEMI_OPEN_FILE),
+                                      (Piece::Queen, QUEEN_OPEN_FILE, QUEEN_SEMI_OPEN_FILE)].iter() {
+        for square in *board.pieces(piece) & own {
+            let file_mask = pawns::file_mask(square.get_file().to_index());
+            if own_pawns & file_mask == 0 {
+                score += if enemy_pawns & file_mask == 0 { open } else { semi_open };
+            }
+        }
+    }
+
+    // The 7th rank only matters if the enemy king is stuck behind it or there are pawns to pick off
+    let seventh = pawns::rank_mask(if color == Color::White { 6 } else { 1 });
+    let eighth = pawns::rank_mask(if color == Color::White { 7 } else { 0 });
+    let enemy_king = BitBoard::from_square(board.king_square(!color)).0;
+    if enemy_pawns & seventh != 0 || enemy_king & eighth != 0 {
+        let rooks = (*board.pieces(Piece::Rook) & own).0 & seventh;
+        score += ROOK_ON_SEVENTH * rooks.count_ones() as i32;
+    }
+
+    // Outposts: squares in the enemy half defended by a pawn that no enemy pawn can ever attack
+    for &(piece, bonus) in [(Piece::Knight, KNIGHT_OUTPOST), (Piece::Bishop, BISHOP_OUTPOST)].iter() {
+        for square in *board.pieces(piece) & own {
+            let bit = BitBoard::from_square(square).0;
+            let rank = square.get_rank().to_index();
+            let relative_rank = if color == Color::White { rank } else { 7 - rank };
+            let attack_span = pawns::adjacent_files(square.get_file().to_index()) & pawns::ranks_ahead(rank, color);
+
+            if relative_rank >= 3 && relative_rank <= 5 && pawn_defended & bit != 0 && enemy_pawns & attack_span == 0 {
+                score += bonus;
+            }
+        }
+    }
+
+    for square in bishops {
+        let colour = if BitBoard::from_square(square).0 & LIGHT_SQUARES != 0 { LIGHT_SQUARES } else { !LIGHT_SQUARES };
+        score += BAD_BISHOP * (own_pawns & colour).count_ones() as i32;
+    }
+
+    score
+}
+
+// Rank of the pawn closest to the king's side of the board out of the given ones
+fn closest_pawn(pawns: u64, color: Color) -> Option<usize> {
+    if pawns == 0 {
+        return None
+    }
+    match color {
+        Color::White => Some(pawns.trailing_zeros() as usize / 8),
+        Color::Black => Some((63 - pawns.leading_zeros()) as usize / 8),
+    }
+}
+
+fn distance(a: usize, b: usize) -> usize {
+    if a > b { a - b } else { b - a }
+}
+
+// Squares a knight, bishop, rook or queen on `square` attacks
+fn piece_attacks(piece: Piece, square: Square, occupied: BitBoard) -> BitBoard {
+    match piece {
+        Piece::Knight => chess::get_knight_moves(square),
+        Piece::Bishop => chess::get_bishop_moves(square, occupied),
+        Piece::Rook => chess::get_rook_moves(square, occupied),
+        _ => chess::get_bishop_moves(square, occupied) | chess::get_rook_moves(square, occupied),
+    }
+}
+
+fn piece_square_tables(piece: Piece, color: Color) -> (&'static [i32; 64], &'static [i32; 64]) {
+    match (color, piece) {
+        (Color::White, Piece::Pawn) => (&pst::PST_WHITE_PAWN_MG, &pst::PST_WHITE_PAWN_EG),
+        (Color::White, Piece::Knight) => (&pst::PST_WHITE_KNIGHT_MG, &pst::PST_WHITE_KNIGHT_EG),
+        (Color::White, Piece::Bishop) => (&pst::PST_WHITE_BISHOP_MG, &pst::PST_WHITE_BISHOP_EG),
+        (Color::White, Piece::Rook) => (&pst::PST_WHITE_ROOK_MG, &pst::PST_WHITE_ROOK_EG),
+        (Color::White, Piece::Queen) => (&pst::PST_WHITE_QUEEN_MG, &pst::PST_WHITE_QUEEN_EG),
+        (Color::White, Piece::King) => (&pst::PST_WHITE_KING_MG, &pst::PST_WHITE_KING_EG),
+        (Color::Black, Piece::Pawn) => (&pst::PST_BLACK_PAWN_MG, &pst::PST_BLACK_PAWN_EG),
+        (Color::Black, Piece::Knight) => (&pst::PST_BLACK_KNIGHT_MG, &pst::PST_BLACK_KNIGHT_EG),
+        (Color::Black, Piece::Bishop) => (&pst::PST_BLACK_BISHOP_MG, &pst::PST_BLACK_BISHOP_EG),
+        (Color::Black, Piece::Rook) => (&pst::PST_BLACK_ROOK_MG, &pst::PST_BLACK_ROOK_EG),
+        (Color::Black, Piece::Queen) => (&pst::PST_BLACK_QUEEN_MG, &pst::PST_BLACK_QUEEN_EG),
+        (Color::Black, Piece::King) => (&pst::PST_BLACK_KING_MG, &pst::PST_BLACK_KING_EG),
+    }
+}
+
+// Also used for exchanges, the king is worth more than everything else
+// put together so it is never traded off
+pub fn piece_value(piece: Piece) -> Score {
+    match piece {
+        Piece::Pawn => 100,
+        Piece::Bishop => 330,
+        Piece::Knight => 320,
+        Piece::Rook => 500,
+        Piece::Queen => 900,
+        Piece::King => 20000,
+    }
+}
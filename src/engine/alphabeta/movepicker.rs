@@ -0,0 +1,215 @@
+use chess::{BitBoard, Board, ChessMove, Color, MoveGen, Piece, Rank, Square, EMPTY};
+use super::ordering::{self, MoveOrdering};
+use super::super::see;
+
+// Moves are handed out one stage at a time and each stage is only generated once the
+// previous one has run out, so a cutoff by an early move saves generating the rest
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Stage {
+    HashMove,
+    GenerateCaptures,
+    Captures,       // Captures and queen promotions that don't lose material
+    Killers,
+    GenerateQuiets,
+    Quiets,
+    BadCaptures,    // Captures that lose material and underpromotions
+    Done,
+}
+
+pub struct MovePicker {
+    board: Board,
+    stage: Stage,
+    hash_move: Option<ChessMove>,
+    killers: [Option<ChessMove>; 2],
+    killer_index: usize,
+    generator: Option<MoveGen>,
+    moves: Vec<(i32, ChessMove)>,          // Moves of the current stage, the best one last
+    bad_captures: Vec<(i32, ChessMove)>,
+    back_rank_quiets: Vec<ChessMove>,      // Quiet moves the captures stage picked up with the promotions
+}
+
+impl MovePicker {
+    pub fn new(board: &Board, hash_move: Option<ChessMove>, ordering: &MoveOrdering, ply: usize) -> MovePicker {
+        MovePicker {
+            board: *board,
+            stage: Stage::HashMove,
+            // The hash move could come from a different position with the same hash
+            hash_move: hash_move.filter(|&m| is_legal(board, m)),
+            killers: ordering.killers(ply),
+            killer_index: 0,
+            generator: None,
+            moves: Vec::new(),
+            bad_captures: Vec::new(),
+            back_rank_quiets: Vec::new(),
+        }
+    }
+
+    // The next move to search. The history scores in `ordering` are only
+    // looked at once the quiet moves are generated
+    pub fn next(&mut self, ordering: &MoveOrdering) -> Option<ChessMove> {
+        loop {
+            match self.stage {
+                Stage::HashMove => {
+                    self.stage = Stage::GenerateCaptures;
+                    if self.hash_move.is_some() {
+                        return self.hash_move
+                    }
+                }
+                Stage::GenerateCaptures => {
+                    let mut generator = MoveGen::new_legal(&self.board);
+                    generator.set_iterator_mask(capture_mask(&self.board));
+                    for m in &mut generator {
+                        if Some(m) == self.hash_move { continue }
+
+                        let is_promotion = m.get_promotion() != None;
+                        if !is_promotion && !ordering::is_capture(&self.board, m) {
+                            self.back_rank_quiets.push(m);
+                            continue
+                        }
+                        let see = see::see(&self.board, m);
+                        if see >= 0 && (m.get_promotion() == Some(Piece::Queen) || !is_promotion) {
+                            self.moves.push((ordering::mvv_lva(&self.board, m), m));
+                        } else {
+                            self.bad_captures.push((see, m));
+                        }
+                    }
+                    self.moves.sort_by_key(|&(score, _)| score);
+                    self.bad_captures.sort_by_key(|&(score, _)| score);
+                    self.generator = Some(generator);
+                    self.stage = Stage::Captures;
+                }
+                Stage::Captures => {
+                    match self.moves.pop() {
+                        Some((_, m)) => return Some(m),
+                        None => self.stage = Stage::Killers,
+                    }
+                }
+                Stage::Killers => {
+                    if self.killer_index >= self.killers.len() {
+                        self.stage = Stage::GenerateQuiets;
+                        continue
+                    }
+                    let killer = self.killers[self.killer_index];
+                    self.killer_index += 1;
+
+                    // Killers come from sibling positions, so they might not be quiet or even legal here
+                    if let Some(m) = killer {
+                        if killer != self.hash_move && m.get_promotion() == None
+                           && !ordering::is_capture(&self.board, m) && is_legal(&self.board, m) {
+                            return killer
+                        }
+                    }
+                }
+                Stage::GenerateQuiets => {
+                    // Everything the captures stage didn't take is a quiet move
+                    let mut quiets: Vec<ChessMove> = self.back_rank_quiets.drain(..).collect();
+                    if let Some(ref mut generator) = self.generator {
+                        generator.set_iterator_mask(!EMPTY);
+                        quiets.extend(generator);
+                    }
+                    for m in quiets {
+                        if Some(m) == self.hash_move || self.killers.contains(&Some(m)) { continue }
+                        self.moves.push((ordering.history_score(&self.board, m), m));
+                    }
+                    self.moves.sort_by_key(|&(score, _)| score);
+                    self.stage = Stage::Quiets;
+                }
+                Stage::Quiets => {
+                    match self.moves.pop() {
+                        Some((_, m)) => return Some(m),
+                        None => self.stage = Stage::BadCaptures,
+                    }
+                }
+                Stage::BadCaptures => {
+                    match self.bad_captures.pop() {
+                        Some((_, m)) => return Some(m),
+                        None => self.stage = Stage::Done,
+                    }
+                }
+                Stage::Done => return None,
+            }
+        }
+    }
+}
+
+// Board::legal() generates every legal move, far too slow for checking the hash move and
+// killers at every node. This only looks at whether the piece can get to the destination
+// and whether the king is safe afterwards. Castling and en passant are left to Board::legal()
+fn is_legal(board: &Board, m: ChessMove) -> bool {
+    let color = board.side_to_move();
+    let source = m.get_source();
+    let dest = m.get_dest();
+    let dest_square = BitBoard::from_square(dest);
+    let own = *board.color_combined(color);
+    let occupied = *board.combined();
+
+    if own & BitBoard::from_square(source) == EMPTY || own & dest_square != EMPTY {
+        return false
+    }
+    let piece = match board.piece_on(source) {
+        Some(piece) => piece,
+        None => return false,
+    };
+
+    // Pawns reaching the last rank have to promote, and nothing else can
+    let promotes = piece == Piece::Pawn && dest.get_rank() == color.to_their_backrank();
+    match m.get_promotion() {
+        None if promotes => return false,
+        Some(Piece::Pawn) | Some(Piece::King) => return false,
+        Some(_) if !promotes => return false,
+        _ => {}
+    }
+
+    let reachable = match piece {
+        Piece::Pawn => {
+            if source.get_file() != dest.get_file() && board.piece_on(dest) == None {
+                return board.legal(m)
+            }
+            chess::get_pawn_quiets(source, color, occupied) | chess::get_pawn_attacks(source, color, *board.color_combined(!color))
+        }
+        Piece::Knight => chess::get_knight_moves(source),
+        Piece::Bishop => chess::get_bishop_moves(source, occupied),
+        Piece::Rook => chess::get_rook_moves(source, occupied),
+        Piece::Queen => chess::get_bishop_moves(source, occupied) | chess::get_rook_moves(source, occupied),
+        Piece::King => {
+            if (source.get_file().to_index() as i32 - dest.get_file().to_index() as i32).abs() == 2 {
+                return board.legal(m)
+            }
+            // The king is taken off the board so it can't hide behind itself from a slider
+            let without_king = occupied ^ BitBoard::from_square(source);
+            return chess::get_king_moves(source) & dest_square != EMPTY
+                && see::attackers_to(board, dest, without_king) & *board.color_combined(!color) == EMPTY
+        }
+    };
+    if reachable & dest_square == EMPTY {
+        return false
+    }
+
+    // A pinned piece can only move along the line through its king
+    let king = board.king_square(color);
+    if *board.pinned() & BitBoard::from_square(source) != EMPTY && chess::line(source, king) & dest_square == EMPTY {
+        return false
+    }
+
+    // In check the move has to take the checking piece or block it, in double check only the king can move
+    let checkers = *board.checkers();
+    match checkers.popcnt() {
+        0 => true,
+        1 => (checkers | chess::between(checkers.to_square(), king)) & dest_square != EMPTY,
+        _ => false,
+    }
+}
+
+// Destination squares of the captures stage: the enemy pieces, the en passant
+// square and the promotion rank
+fn capture_mask(board: &Board) -> BitBoard {
+    let color = board.side_to_move();
+    let mut mask = *board.color_combined(!color) | chess::get_rank(color.to_their_backrank());
+
+    // en_passant() is the square of the pawn that can be taken, not where the capturing pawn lands
+    if let Some(square) = board.en_passant() {
+        let rank = if color == Color::White { Rank::Sixth } else { Rank::Third };
+        mask |= BitBoard::from_square(Square::make_square(rank, square.get_file()));
+    }
+    mask
+}
@@ -0,0 +1,126 @@
+use chess::{Board, ChessMove, Piece};
+use super::MAX_PLY;
+use super::super::see;
+
+// Moves are searched from the highest score to the lowest
+const HASH_MOVE_SCORE: i32 = 1_000_000;
+const CAPTURE_SCORE: i32 = 100_000;
+const KILLER_SCORES: [i32; 2] = [90_000, 80_000];
+
+// History scores are halved once any of them reaches this, so they stay below the killers
+const HISTORY_MAX: i32 = 50_000;
+
+// Remembers which quiet moves caused beta cutoffs so they can be tried early
+// in other parts of the tree
+pub struct MoveOrdering {
+    killers: [[Option<ChessMove>; 2]; MAX_PLY],
+    history: [[[i32; 64]; 64]; 2],     // [color][source][dest]
+}
+
+impl MoveOrdering {
+    pub fn new() -> MoveOrdering {
+        MoveOrdering {
+            killers: [[None; 2]; MAX_PLY],
+            history: [[[0; 64]; 64]; 2],
+        }
+    }
+
+    // Sort the moves: hash move, captures (MVV-LVA), killer moves, then quiets by history
+    pub fn order_moves(&self, board: &Board, moves: &mut Vec<ChessMove>, hash_move: Option<ChessMove>, ply: usize) {
+        let mut scored: Vec<(i32, ChessMove)> = moves.iter()
+            .map(|&m| (self.score_move(board, m, hash_move, ply), m))
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0));
+
+        moves.clear();
+        moves.extend(scored.into_iter().map(|(_, m)| m));
+    }
+
+    fn score_move(&self, board: &Board, m: ChessMove, hash_move: Option<ChessMove>, ply: usize) -> i32 {
+        if Some(m) == hash_move {
+            return HASH_MOVE_SCORE
+        }
+        // Captures that lose material on the exchange go after the quiet moves
+        if is_capture(board, m) || m.get_promotion() == Some(Piece::Queen) {
+            let see = see::see(board, m);
+            return if see >= 0 { CAPTURE_SCORE + mvv_lva(board, m) } else { see }
+        }
+        if self.killers[ply][0] == Some(m) {
+            return KILLER_SCORES[0]
+        }
+        if self.killers[ply][1] == Some(m) {
+            return KILLER_SCORES[1]
+        }
+
+        self.history_score(board, m)
+    }
+
+    pub fn history_score(&self, board: &Board, m: ChessMove) -> i32 {
+        let color = board.side_to_move().to_index();
+        self.history[color][m.get_source().to_index()][m.get_dest().to_index()]
+    }
+
+    pub fn killers(&self, ply: usize) -> [Option<ChessMove>; 2] {
+        self.killers[ply]
+    }
+
+    pub fn is_killer(&self, m: ChessMove, ply: usize) -> bool {
+        self.killers[ply][0] == Some(m) || self.killers[ply][1] == Some(m)
+    }
+
+    // Called when a quiet move causes a beta cutoff
+    pub fn update(&mut self, board: &Board, m: ChessMove, depth: u32, ply: usize) {
+        if self.killers[ply][0] != Some(m) {
+            self.killers[ply][1] = self.killers[ply][0];
+            self.killers[ply][0] = Some(m);
+        }
+
+        let color = board.side_to_move().to_index();
+        let entry = &mut self.history[color][m.get_source().to_index()][m.get_dest().to_index()];
+        *entry += (depth * depth) as i32;
+
+        if *entry >= HISTORY_MAX {
+            self.halve_history();
+        }
+    }
+
+    // Called before every search. The killers belong to the plies of a different
+    // root, and the history is aged so the most recent searches count for more
+    pub fn new_search(&mut self) {
+        self.killers = [[None; 2]; MAX_PLY];
+        self.halve_history();
+    }
+
+    fn halve_history(&mut self) {
+        for side in self.history.iter_mut() {
+            for from in side.iter_mut() {
+                for value in from.iter_mut() {
+                    *value /= 2;
+                }
+            }
+        }
+    }
+}
+
+pub fn is_capture(board: &Board, m: ChessMove) -> bool {
+    board.piece_on(m.get_dest()) != None || is_en_passant(board, m)
+}
+
+// A pawn moving diagonally onto an empty square
+fn is_en_passant(board: &Board, m: ChessMove) -> bool {
+    board.piece_on(m.get_source()) == Some(Piece::Pawn)
+        && m.get_source().get_file() != m.get_dest().get_file()
+        && board.piece_on(m.get_dest()) == None
+}
+
+// Most Valuable Victim - Least Valuable Attacker
+pub fn mvv_lva(board: &Board, m: ChessMove) -> i32 {
+    let victim = match board.piece_on(m.get_dest()) {
+        Some(piece) => piece,
+        None if m.get_promotion() != None => Piece::Queen,
+        None => Piece::Pawn,   // En passant
+    };
+    let attacker = board.piece_on(m.get_source()).unwrap_or(Piece::Pawn);
+
+    10 * victim.to_index() as i32 - attacker.to_index() as i32
+}
@@ -0,0 +1,200 @@
+use chess::{Board, Color, Piece};
+use super::eval::Tapered;
+
+// Number of entries in the pawn hash table, a power of two
+const PAWN_TABLE_SIZE: usize = 16384;
+
+const DOUBLED: Tapered = Tapered::new(-10, -25);    // For every pawn with another one in front of it
+const ISOLATED: Tapered = Tapered::new(-12, -15);   // No friendly pawns on the files next to it
+const BACKWARD: Tapered = Tapered::new(-8, -10);    // Can't be defended by a pawn and can't safely advance
+
+// Bonuses by rank counted from the pawn's own side of the board
+const CONNECTED: [Tapered; 8] = [
+    Tapered::new(0, 0), Tapered::new(3, 2), Tapered::new(5, 4), Tapered::new(8, 6),
+    Tapered::new(14, 12), Tapered::new(25, 25), Tapered::new(40, 40), Tapered::new(0, 0),
+];
+const PASSED: [Tapered; 8] = [
+    Tapered::new(0, 0), Tapered::new(5, 10), Tapered::new(10, 15), Tapered::new(15, 25),
+    Tapered::new(25, 45), Tapered::new(40, 75), Tapered::new(60, 120), Tapered::new(0, 0),
+];
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+const FILE_H: u64 = FILE_A << 7;
+
+#[derive(Clone, Copy)]
+struct PawnEntry {
+    key: u64,
+    score: Tapered,     // Everything except the passed pawn bonuses, from White's point of view
+    passed: u64,        // Passed pawns of both sides
+}
+
+// The pawn structure changes far less often than the rest of the board, so its score
+// is cached by a hash of the pawns alone. Passed pawns are scored outside of the cache
+// because whether they are blocked depends on the other pieces
+pub struct PawnTable {
+    entries: Vec<Option<PawnEntry>>,
+}
+
+impl PawnTable {
+    pub fn new() -> PawnTable {
+        PawnTable {
+            entries: vec![None; PAWN_TABLE_SIZE],
+        }
+    }
+
+    // Pawn structure score from White's point of view
+    pub fn evaluate(&mut self, board: &Board) -> Tapered {
+        let key = pawn_key(board);
+        let index = (key as usize) & (PAWN_TABLE_SIZE - 1);
+
+        let entry = match self.entries[index] {
+            Some(entry) if entry.key == key => entry,
+            _ => {
+                let entry = pawn_structure(board, key);
+                self.entries[index] = Some(entry);
+                entry
+            }
+        };
+
+        let mut score = entry.score;
+        score += passed_pawns(board, entry.passed, Color::White);
+        score -= passed_pawns(board, entry.passed, Color::Black);
+        score
+    }
+}
+
+// chess::Board::get_pawn_hash() isn't implemented (it is always 0), so the key is
+// made by mixing the bits of both sides' pawns
+fn pawn_key(board: &Board) -> u64 {
+    let white = (*board.pieces(Piece::Pawn) & *board.color_combined(Color::White)).0;
+    let black = (*board.pieces(Piece::Pawn) & *board.color_combined(Color::Black)).0;
+    mix(white ^ mix(black))
+}
+
+// Finalizer of the splitmix64 generator, every input bit affects every output bit
+fn mix(x: u64) -> u64 {
+    let mut x = x;
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+fn pawn_structure(board: &Board, key: u64) -> PawnEntry {
+    let (white, white_passed) = pawn_terms(board, Color::White);
+    let (black, black_passed) = pawn_terms(board, Color::Black);
+
+    let mut score = white;
+    score -= black;
+    PawnEntry {
+        key: key,
+        score: score,
+        passed: white_passed | black_passed,
+    }
+}
+
+// Structure score of one side's pawns and which of them are passed
+fn pawn_terms(board: &Board, color: Color) -> (Tapered, u64) {
+    let own = (*board.pieces(Piece::Pawn) & *board.color_combined(color)).0;
+    let enemy = (*board.pieces(Piece::Pawn) & *board.color_combined(!color)).0;
+    let own_attacks = pawn_attacks(own, color);
+    let enemy_attacks = pawn_attacks(enemy, !color);
+
+    let mut score = Tapered::default();
+    let mut passed = 0;
+    let mut pawns = own;
+    while pawns != 0 {
+        let square = pawns.trailing_zeros() as usize;
+        pawns &= pawns - 1;
+        let bit = 1u64 << square;
+        let file = square % 8;
+        let rank = square / 8;
+        let relative_rank = if color == Color::White { rank } else { 7 - rank };
+        let ahead = ranks_ahead(rank, color);
+        let neighbours = adjacent_files(file);
+
+        let doubled = own & file_mask(file) & ahead != 0;
+        let isolated = own & neighbours == 0;
+        let supported = own_attacks & bit != 0;
+        let phalanx = own & neighbours & rank_mask(rank) != 0;
+
+        if doubled {
+            score += DOUBLED;
+        }
+        if isolated {
+            score += ISOLATED;
+        }
+        if supported || phalanx {
+            score += CONNECTED[relative_rank];
+        }
+
+        // Backward: every friendly pawn on the files next to it is further up the board,
+        // and the square in front of it is controlled by an enemy pawn
+        let stop_square = if color == Color::White { bit << 8 } else { bit >> 8 };
+        if !isolated && !supported && !phalanx && own & neighbours & !ahead == 0 && enemy_attacks & stop_square != 0 {
+            score += BACKWARD;
+        }
+
+        // Passed: no enemy pawns in front of it on its own or the neighbouring files.
+        // Only the front pawn of a doubled pair counts
+        if !doubled && enemy & (file_mask(file) | neighbours) & ahead == 0 {
+            passed |= bit;
+        }
+    }
+
+    (score, passed)
+}
+
+// Passed pawns are worth more the further they have advanced,
+// but only half as much while something is standing in their way
+fn passed_pawns(board: &Board, passed: u64, color: Color) -> Tapered {
+    let occupied = board.combined().0;
+    let mut pawns = passed & board.color_combined(color).0;
+    let mut score = Tapered::default();
+
+    while pawns != 0 {
+        let square = pawns.trailing_zeros() as usize;
+        pawns &= pawns - 1;
+        let rank = square / 8;
+        let relative_rank = if color == Color::White { rank } else { 7 - rank };
+        let path = file_mask(square % 8) & ranks_ahead(rank, color);
+
+        let bonus = PASSED[relative_rank];
+        if path & occupied != 0 {
+            score += Tapered::new(bonus.mg / 2, bonus.eg / 2);
+        } else {
+            score += bonus;
+        }
+    }
+    score
+}
+
+pub fn file_mask(file: usize) -> u64 {
+    FILE_A << file
+}
+
+pub fn rank_mask(rank: usize) -> u64 {
+    0xff << (8 * rank)
+}
+
+pub fn adjacent_files(file: usize) -> u64 {
+    let left = if file > 0 { file_mask(file - 1) } else { 0 };
+    let right = if file < 7 { file_mask(file + 1) } else { 0 };
+    left | right
+}
+
+// Every square on the ranks in front of `rank` as seen by `color`
+pub fn ranks_ahead(rank: usize, color: Color) -> u64 {
+    match color {
+        Color::White if rank >= 7 => 0,
+        Color::White => !0 << (8 * (rank + 1)),
+        Color::Black => (1 << (8 * rank)) - 1,
+    }
+}
+
+// Squares attacked by all of the given pawns at once
+pub fn pawn_attacks(pawns: u64, color: Color) -> u64 {
+    match color {
+        Color::White => ((pawns << 7) & !FILE_H) | ((pawns << 9) & !FILE_A),
+        Color::Black => ((pawns >> 9) & !FILE_H) | ((pawns >> 7) & !FILE_A),
+    }
+}
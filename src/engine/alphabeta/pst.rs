@@ -1,4 +1,4 @@
-pub const PST_WHITE_PAWN: [i32; 64] = 
+pub const PST_WHITE_PAWN_MG: [i32; 64] = 
 [
   //A1                        //H1
     0,  0,  0,  0,  0,  0,  0,  0,
@@ -12,7 +12,7 @@ pub const PST_WHITE_PAWN: [i32; 64] =
   //A8                        //H8
 ];
 
-pub const PST_WHITE_KNIGHT: [i32; 64] = 
+pub const PST_WHITE_KNIGHT_MG: [i32; 64] = 
 [
   //A1                        //H1
   -50,-40,-30,-30,-30,-30,-40,-50,
@@ -26,7 +26,7 @@ pub const PST_WHITE_KNIGHT: [i32; 64] =
   //A8                        //H8
 ];
 
-pub const PST_WHITE_BISHOP: [i32; 64] = 
+pub const PST_WHITE_BISHOP_MG: [i32; 64] = 
 [
   //A1                        //H1
   -20,-10,-10,-10,-10,-10,-10,-20,
@@ -40,7 +40,7 @@ pub const PST_WHITE_BISHOP: [i32; 64] =
   //A8                        //H8
 ];
 
-pub const PST_WHITE_ROOK: [i32; 64] = 
+pub const PST_WHITE_ROOK_MG: [i32; 64] = 
 [
   //A1                        //H1
     0,  0,  0,  5,  5,  0,  0,  0,
@@ -54,7 +54,7 @@ pub const PST_WHITE_ROOK: [i32; 64] =
   //A8                        //H8
 ];
 
-pub const PST_WHITE_QUEEN: [i32; 64] = 
+pub const PST_WHITE_QUEEN_MG: [i32; 64] = 
 [
   //A1                        //H1
   -20,-10,-10, -5, -5,-10,-10,-20,
@@ -68,7 +68,7 @@ pub const PST_WHITE_QUEEN: [i32; 64] =
   //A8                        //H8
 ];
 
-pub const PST_WHITE_KING: [i32; 64] = 
+pub const PST_WHITE_KING_MG: [i32; 64] = 
 [
   //A1                        //H1
    20, 30, 10,  0,  0, 10, 30, 20,
@@ -82,7 +82,92 @@ pub const PST_WHITE_KING: [i32; 64] =
   //A8                        //H8
 ];
 
-pub const PST_BLACK_PAWN: [i32; 64] = 
+// Endgame tables: the king heads for the centre and pawns are worth more the closer they get to promoting
+pub const PST_WHITE_PAWN_EG: [i32; 64] = 
+[
+  //A1                        //H1
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    5,  5,  5,  5,  5,  5,  5,  5,
+   10, 10, 10, 10, 10, 10, 10, 10,
+   20, 20, 20, 20, 20, 20, 20, 20,
+   35, 35, 35, 35, 35, 35, 35, 35,
+   60, 60, 60, 60, 60, 60, 60, 60,
+    0,  0,  0,  0,  0,  0,  0,  0
+  //A8                        //H8
+];
+
+pub const PST_WHITE_KNIGHT_EG: [i32; 64] = 
+[
+  //A1                        //H1
+  -50,-40,-30,-30,-30,-30,-40,-50,
+  -40,-20,-10, -5, -5,-10,-20,-40,
+  -30,-10, 10, 15, 15, 10,-10,-30,
+  -30, -5, 15, 20, 20, 15, -5,-30,
+  -30, -5, 15, 20, 20, 15, -5,-30,
+  -30,-10, 10, 15, 15, 10,-10,-30,
+  -40,-20,-10, -5, -5,-10,-20,-40,
+  -50,-40,-30,-30,-30,-30,-40,-50
+  //A8                        //H8
+];
+
+pub const PST_WHITE_BISHOP_EG: [i32; 64] = 
+[
+  //A1                        //H1
+  -20,-10,-10,-10,-10,-10,-10,-20,
+  -10,  0,  0,  0,  0,  0,  0,-10,
+  -10,  0,  5,  5,  5,  5,  0,-10,
+  -10,  0,  5, 10, 10,  5,  0,-10,
+  -10,  0,  5, 10, 10,  5,  0,-10,
+  -10,  0,  5,  5,  5,  5,  0,-10,
+  -10,  0,  0,  0,  0,  0,  0,-10,
+  -20,-10,-10,-10,-10,-10,-10,-20
+  //A8                        //H8
+];
+
+pub const PST_WHITE_ROOK_EG: [i32; 64] = 
+[
+  //A1                        //H1
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0,
+   10, 10, 10, 10, 10, 10, 10, 10,
+    5,  5,  5,  5,  5,  5,  5,  5
+  //A8                        //H8
+];
+
+pub const PST_WHITE_QUEEN_EG: [i32; 64] = 
+[
+  //A1                        //H1
+  -20,-10,-10, -5, -5,-10,-10,-20,
+  -10,  0,  0,  0,  0,  0,  0,-10,
+  -10,  0,  5,  5,  5,  5,  0,-10,
+   -5,  0,  5, 10, 10,  5,  0, -5,
+   -5,  0,  5, 10, 10,  5,  0, -5,
+  -10,  0,  5,  5,  5,  5,  0,-10,
+  -10,  0,  0,  0,  0,  0,  0,-10,
+  -20,-10,-10, -5, -5,-10,-10,-20
+  //A8                        //H8
+];
+
+pub const PST_WHITE_KING_EG: [i32; 64] = 
+[
+  //A1                        //H1
+  -50,-30,-30,-30,-30,-30,-30,-50,
+  -30,-30,  0,  0,  0,  0,-30,-30,
+  -30,-10, 20, 30, 30, 20,-10,-30,
+  -30,-10, 30, 40, 40, 30,-10,-30,
+  -30,-10, 30, 40, 40, 30,-10,-30,
+  -30,-10, 20, 30, 30, 20,-10,-30,
+  -30,-20,-10,  0,  0,-10,-20,-30,
+  -50,-40,-30,-20,-20,-30,-40,-50
+  //A8                        //H8
+];
+
+pub const PST_BLACK_PAWN_MG: [i32; 64] = 
 [
   //A1                        //H1
     0,  0,  0,  0,  0,  0,  0,  0,
@@ -96,7 +181,7 @@ pub const PST_BLACK_PAWN: [i32; 64] =
   //A8                        //H8
 ];
 
-pub const PST_BLACK_KNIGHT: [i32; 64] = 
+pub const PST_BLACK_KNIGHT_MG: [i32; 64] = 
 [
   //A1                        //H1
   -50,-40,-30,-30,-30,-30,-40,-50,
@@ -110,7 +195,7 @@ pub const PST_BLACK_KNIGHT: [i32; 64] =
   //A8                        //H8
 ];
 
-pub const PST_BLACK_BISHOP: [i32; 64] = 
+pub const PST_BLACK_BISHOP_MG: [i32; 64] = 
 [
   //A1                        //H1
   -20,-10,-10,-10,-10,-10,-10,-20,
@@ -124,7 +209,7 @@ pub const PST_BLACK_BISHOP: [i32; 64] =
   //A8                        //H8
 ];
 
-pub const PST_BLACK_ROOK: [i32; 64] = 
+pub const PST_BLACK_ROOK_MG: [i32; 64] = 
 [
   //A1                        //H1
     0,  0,  0,  0,  0,  0,  0,  0,
@@ -138,7 +223,7 @@ pub const PST_BLACK_ROOK: [i32; 64] =
   //A8                        //H8
 ];
 
-pub const PST_BLACK_QUEEN: [i32; 64] = 
+pub const PST_BLACK_QUEEN_MG: [i32; 64] = 
 [
   //A1                        //H1
   -20,-10,-10, -5, -5,-10,-10,-20,
@@ -152,7 +237,7 @@ pub const PST_BLACK_QUEEN: [i32; 64] =
   //A8                        //H8
 ];
 
-pub const PST_BLACK_KING: [i32; 64] = 
+pub const PST_BLACK_KING_MG: [i32; 64] = 
 [
   //A1                        //H1
   -30,-40,-40,-50,-50,-40,-40,-30,
@@ -164,4 +249,88 @@ pub const PST_BLACK_KING: [i32; 64] =
    20, 20,  0,  0,  0,  0, 20, 20,
    20, 30, 10,  0,  0, 10, 30, 20
   //A8                        //H8
-];
\ No newline at end of file
+];
+
+pub const PST_BLACK_PAWN_EG: [i32; 64] = 
+[
+  //A1                        //H1
+    0,  0,  0,  0,  0,  0,  0,  0,
+   60, 60, 60, 60, 60, 60, 60, 60,
+   35, 35, 35, 35, 35, 35, 35, 35,
+   20, 20, 20, 20, 20, 20, 20, 20,
+   10, 10, 10, 10, 10, 10, 10, 10,
+    5,  5,  5,  5,  5,  5,  5,  5,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0
+  //A8                        //H8
+];
+
+pub const PST_BLACK_KNIGHT_EG: [i32; 64] = 
+[
+  //A1                        //H1
+  -50,-40,-30,-30,-30,-30,-40,-50,
+  -40,-20,-10, -5, -5,-10,-20,-40,
+  -30,-10, 10, 15, 15, 10,-10,-30,
+  -30, -5, 15, 20, 20, 15, -5,-30,
+  -30, -5, 15, 20, 20, 15, -5,-30,
+  -30,-10, 10, 15, 15, 10,-10,-30,
+  -40,-20,-10, -5, -5,-10,-20,-40,
+  -50,-40,-30,-30,-30,-30,-40,-50
+  //A8                        //H8
+];
+
+pub const PST_BLACK_BISHOP_EG: [i32; 64] = 
+[
+  //A1                        //H1
+  -20,-10,-10,-10,-10,-10,-10,-20,
+  -10,  0,  0,  0,  0,  0,  0,-10,
+  -10,  0,  5,  5,  5,  5,  0,-10,
+  -10,  0,  5, 10, 10,  5,  0,-10,
+  -10,  0,  5, 10, 10,  5,  0,-10,
+  -10,  0,  5,  5,  5,  5,  0,-10,
+  -10,  0,  0,  0,  0,  0,  0,-10,
+  -20,-10,-10,-10,-10,-10,-10,-20
+  //A8                        //H8
+];
+
+pub const PST_BLACK_ROOK_EG: [i32; 64] = 
+[
+  //A1                        //H1
+    5,  5,  5,  5,  5,  5,  5,  5,
+   10, 10, 10, 10, 10, 10, 10, 10,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0
+  //A8                        //H8
+];
+
+pub const PST_BLACK_QUEEN_EG: [i32; 64] = 
+[
+  //A1                        //H1
+  -20,-10,-10, -5, -5,-10,-10,-20,
+  -10,  0,  0,  0,  0,  0,  0,-10,
+  -10,  0,  5,  5,  5,  5,  0,-10,
+   -5,  0,  5, 10, 10,  5,  0, -5,
+   -5,  0,  5, 10, 10,  5,  0, -5,
+  -10,  0,  5,  5,  5,  5,  0,-10,
+  -10,  0,  0,  0,  0,  0,  0,-10,
+  -20,-10,-10, -5, -5,-10,-10,-20
+  //A8                        //H8
+];
+
+pub const PST_BLACK_KING_EG: [i32; 64] = 
+[
+  //A1                        //H1
+  -50,-40,-30,-20,-20,-30,-40,-50,
+  -30,-20,-10,  0,  0,-10,-20,-30,
+  -30,-10, 20, 30, 30, 20,-10,-30,
+  -30,-10, 30, 40, 40, 30,-10,-30,
+  -30,-10, 30, 40, 40, 30,-10,-30,
+  -30,-10, 20, 30, 30, 20,-10,-30,
+  -30,-30,  0,  0,  0,  0,-30,-30,
+  -50,-30,-30,-30,-30,-30,-30,-50
+  //A8                        //H8
+];
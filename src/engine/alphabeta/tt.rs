@@ -0,0 +1,232 @@
+use chess::{Board, ChessMove, Piece, ALL_SQUARES};
+use std::cmp;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+// Generations are stored in 6 bits and wrap around
+const GENERATIONS: u8 = 64;
+
+// Within the same search a different position only replaces an entry
+// that is at most this much deeper
+const REPLACE_DEPTH_MARGIN: u32 = 3;
+
+// What the stored score tells us about the real value of the position
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Bound {
+    Exact,  // Score is the exact value
+    Lower,  // Failed high, the real value is at least score
+    Upper,  // Failed low, the real value is at most score
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Entry {
+    pub best_move: Option<ChessMove>,
+    pub score: i32,
+    pub depth: u8,
+    pub bound: Bound,
+}
+
+// Each slot stores the entry packed into a single u64 along with the key xor'ed
+// with that data. If two threads write the same slot at once the key won't match
+// anymore and the entry is ignored, so the table can be shared without locks
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+// Fixed size hash table of previously searched positions indexed by the
+// zobrist hash of the board, shared by all of the search threads. It is kept
+// between moves, every search bumps the generation so old entries can be told apart
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    generation: AtomicU8,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> TranspositionTable {
+        let bytes = size_mb * 1024 * 1024;
+        let mut count = cmp::max(bytes / std::mem::size_of::<Slot>(), 1);
+
+        // Round down to a power of two so the index is just a bit mask
+        if !count.is_power_of_two() {
+            count = count.next_power_of_two() / 2;
+        }
+
+        TranspositionTable {
+            slots: (0..count).map(|_| Slot { key: AtomicU64::new(0), data: AtomicU64::new(0) }).collect(),
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    // Called before every search
+    pub fn new_search(&self) {
+        let generation = self.generation.load(Ordering::Relaxed);
+        self.generation.store((generation + 1) % GENERATIONS, Ordering::Relaxed);
+    }
+
+    // Forget everything, for a new game
+    pub fn clear(&self) {
+        for slot in self.slots.iter() {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key as usize) & (self.slots.len() - 1)
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        let slot = &self.slots[self.index(key)];
+        let data = slot.data.load(Ordering::Relaxed);
+        let stored_key = slot.key.load(Ordering::Relaxed);
+
+        // Empty slots have data == 0, see pack()
+        if data == 0 || stored_key ^ data != key {
+            return None
+        }
+        Some(unpack(data))
+    }
+
+    // Replacement scheme: entries from earlier searches are always replaced. Otherwise
+    // a different position replaces the old entry unless it is a lot shallower, and the
+    // same position is only replaced by a search that is at least as deep
+    pub fn store(&self, key: u64, best_move: Option<ChessMove>, score: i32, depth: u32, bound: Bound) {
+        let mut best_move = best_move;
+        let generation = self.generation.load(Ordering::Relaxed);
+        let slot = &self.slots[self.index(key)];
+        let old_data = slot.data.load(Ordering::Relaxed);
+        let old_key = slot.key.load(Ordering::Relaxed) ^ old_data;
+
+        if old_data != 0 && generation_of(old_data) == generation {
+            let old = unpack(old_data);
+            if old_key != key {
+                if depth + REPLACE_DEPTH_MARGIN < old.depth as u32 {
+                    return
+                }
+            } else if (depth as u8) < old.depth && bound != Bound::Exact {
+                return
+            }
+        }
+        // Don't throw away a good hash move when failing low
+        if best_move == None && old_data != 0 && old_key == key {
+            best_move = unpack(old_data).best_move;
+        }
+
+        let data = pack(&Entry {
+            best_move: best_move,
+            score: score,
+            depth: cmp::min(depth, 255) as u8,
+            bound: bound,
+        }) | (generation as u64) << 58;
+        slot.key.store(key ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    // Permill of the table used by the current search, estimated from the first 1000 slots
+    pub fn hashfull(&self) -> u32 {
+        let generation = self.generation.load(Ordering::Relaxed);
+        let sample = cmp::min(self.slots.len(), 1000);
+        let used = self.slots[..sample].iter()
+            .map(|slot| slot.data.load(Ordering::Relaxed))
+            .filter(|&data| data != 0 && generation_of(data) == generation)
+            .count();
+        (used * 1000 / sample) as u32
+    }
+
+    // Follow the hash moves from the given position to get the principal variation
+    pub fn get_pv(&self, board: Board, max_length: usize) -> Vec<ChessMove> {
+        let mut pv = Vec::new();
+        let mut board = board;
+        let mut seen = Vec::new();
+
+        while pv.len() < max_length {
+            let m = match self.probe(board.get_hash()) {
+                Some(Entry { best_move: Some(m), .. }) => m,
+                _ => break,
+            };
+            // Guard against hash collisions
+            if !board.legal(m) { break }
+
+            // Stop if the line starts repeating itself
+            seen.push(board.get_hash());
+            board = board.make_move_new(m);
+            pv.push(m);
+            if seen.contains(&board.get_hash()) { break }
+        }
+
+        pv
+    }
+}
+
+// Layout of the packed data:
+//   bits  0-15  best move (0 if there is none)
+//   bits 16-47  score
+//   bits 48-55  depth
+//   bits 56-57  bound (1-3, so a used slot never packs to 0)
+//   bits 58-63  generation, added by store()
+fn pack(entry: &Entry) -> u64 {
+    let bound: u64 = match entry.bound {
+        Bound::Exact => 1,
+        Bound::Lower => 2,
+        Bound::Upper => 3,
+    };
+
+    encode_move(entry.best_move) as u64
+        | (entry.score as u32 as u64) << 16
+        | (entry.depth as u64) << 48
+        | bound << 56
+}
+
+fn generation_of(data: u64) -> u8 {
+    (data >> 58) as u8
+}
+
+fn unpack(data: u64) -> Entry {
+    let bound = match (data >> 56) & 3 {
+        1 => Bound::Exact,
+        2 => Bound::Lower,
+        _ => Bound::Upper,
+    };
+
+    Entry {
+        best_move: decode_move(data as u16),
+        score: (data >> 16) as u32 as i32,
+        depth: (data >> 48) as u8,
+        bound: bound,
+    }
+}
+
+// Source square in bits 0-5, destination in bits 6-11 and promotion piece in bits 12-14.
+// A1 to A1 is never a legal move, so 0 can mean no move
+fn encode_move(m: Option<ChessMove>) -> u16 {
+    match m {
+        None => 0,
+        Some(m) => {
+            let promotion: u16 = match m.get_promotion() {
+                None => 0,
+                Some(Piece::Knight) => 1,
+                Some(Piece::Bishop) => 2,
+                Some(Piece::Rook) => 3,
+                Some(_) => 4,
+            };
+            m.get_source().to_index() as u16 | (m.get_dest().to_index() as u16) << 6 | promotion << 12
+        }
+    }
+}
+
+fn decode_move(bits: u16) -> Option<ChessMove> {
+    if bits == 0 {
+        return None
+    }
+    let promotion = match (bits >> 12) & 7 {
+        1 => Some(Piece::Knight),
+        2 => Some(Piece::Bishop),
+        3 => Some(Piece::Rook),
+        4 => Some(Piece::Queen),
+        _ => None,
+    };
+    let source = ALL_SQUARES[(bits & 63) as usize];
+    let dest = ALL_SQUARES[((bits >> 6) & 63) as usize];
+    Some(ChessMove::new(source, dest, promotion))
+}
@@ -0,0 +1,74 @@
+use chess::ChessMove;
+use std::cmp;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+// Deepest iteration the search will start when no depth limit is given
+pub const MAX_DEPTH: u32 = 64;
+
+// Time kept back so the engine never loses on time because of overhead
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+
+// Without knowing how many moves are left, assume the remaining time
+// has to last for this many more moves
+const EXPECTED_MOVES_LEFT: u32 = 30;
+
+// When to stop searching. Any combination of limits can be set and the
+// search stops at the first one that is reached. With no limits set the
+// search runs until it reaches MAX_DEPTH or is stopped from outside
+#[derive(Clone, Default, Debug)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub move_time: Option<Duration>,    // Think for exactly this long
+    pub time_left: Option<Duration>,    // Time left on the engine's clock
+    pub increment: Duration,            // Added to the engine's clock after every move
+    pub infinite: bool,                 // Ignore the clock and search until stopped
+    // Restrict the root moves. If none are left to search (ie. every move is excluded
+    // or the search moves are all illegal) the search returns no move
+    pub search_moves: Vec<ChessMove>,   // Only search these root moves (all of them if empty)
+    pub exclude_moves: Vec<ChessMove>,  // Never search these root moves
+
+    // Set while searching the position after the opponent's expected move. The clock
+    // only starts once the caller clears it (a ponder hit), until then the search
+    // keeps going and won't return. On a miss the caller stops the search instead
+    pub ponder: Option<Arc<AtomicBool>>,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u32) -> SearchLimits {
+        SearchLimits { depth: Some(depth), ..SearchLimits::default() }
+    }
+
+    pub fn move_time(move_time: Duration) -> SearchLimits {
+        SearchLimits { move_time: Some(move_time), ..SearchLimits::default() }
+    }
+
+    pub fn infinite() -> SearchLimits {
+        SearchLimits { infinite: true, ..SearchLimits::default() }
+    }
+
+    pub fn max_depth(&self) -> u32 {
+        match self.depth {
+            Some(depth) => cmp::min(cmp::max(depth, 1), MAX_DEPTH),
+            None => MAX_DEPTH,
+        }
+    }
+
+    // How long this move is allowed to take, None means no time limit
+    pub fn time_budget(&self) -> Option<Duration> {
+        if self.infinite {
+            return None
+        }
+        if let Some(move_time) = self.move_time {
+            return Some(move_time)
+        }
+
+        self.time_left.map(|time_left| {
+            let usable = if time_left > MOVE_OVERHEAD { time_left - MOVE_OVERHEAD } else { Duration::from_millis(0) };
+            let budget = time_left / EXPECTED_MOVES_LEFT + self.increment * 3 / 4;
+            cmp::min(budget, usable)
+        })
+    }
+}
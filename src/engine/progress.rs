@@ -0,0 +1,59 @@
+use chess::ChessMove;
+use std::fmt;
+use std::time::Duration;
+use super::score::{self, Score};
+use super::stats::SearchStats;
+
+// One of the lines found by the search, pv[0] is the root move
+#[derive(Clone, Debug)]
+pub struct PvLine {
+    pub score: Score,
+    pub pv: Vec<ChessMove>,
+}
+
+// What the search found. With MultiPV there is a line for each of the best
+// root moves, sorted from best to worst. If the game is already over there
+// is no move and no lines, and the score is the mate or draw. The same goes
+// when the limits leave no root move to search, with a score of 0
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub best_move: Option<ChessMove>,
+    pub score: Score,
+    pub lines: Vec<PvLine>,
+    pub nodes: u64,             // Nodes searched by all threads
+    pub stats: SearchStats,
+}
+
+impl SearchResult {
+    // The reply the engine expects, worth pondering on while the opponent thinks
+    pub fn ponder_move(&self) -> Option<ChessMove> {
+        self.lines.get(0).and_then(|line| line.pv.get(1).cloned())
+    }
+}
+
+// A snapshot of how the search is going. After every completed iteration one
+// is sent for each line, while an iteration is still running only the best
+// line is sent every so often. The depth, score and pv always come from the
+// last completed iteration, the rest is up to date
+#[derive(Clone, Debug)]
+pub struct SearchProgress {
+    pub depth: u32,             // Depth of the last completed iteration
+    pub seldepth: u32,          // Deepest ply reached including extensions and quiescence
+    pub multipv: u32,           // Which line this is, 1 is the best
+    pub score: Score,           // Score of the line in the last completed iteration
+    pub nodes: u64,             // Nodes searched by all threads
+    pub nps: u64,
+    pub time: Duration,
+    pub hashfull: u32,          // Permill of the transposition table in use
+    pub pv: Vec<ChessMove>,
+    pub completed: bool,        // False if the iteration at `depth` is still running
+}
+
+impl fmt::Display for SearchProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pv: Vec<String> = self.pv.iter().map(|m| m.to_string()).collect();
+        write!(f, "depth {} seldepth {} multipv {} score {} nodes {} nps {} hashfull {} time {:.2?} pv {}",
+               self.depth, self.seldepth, self.multipv, score::to_string(self.score), self.nodes,
+               self.nps, self.hashfull, self.time, pv.join(" "))
+    }
+}
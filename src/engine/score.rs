@@ -0,0 +1,69 @@
+use super::alphabeta::MAX_PLY;
+
+// Scores are in centipawns from the point of view of the side to move.
+// Checkmate is scored as MATE minus the number of plies it takes to get
+// there, so a quick mate is always worth more than a slow one.
+pub type Score = i32;
+
+pub const DRAW: Score = 0;
+pub const MATE: Score = 100000;
+pub const INFINITY: Score = MATE + 1;
+
+// Anything beyond this is a forced mate rather than a material score
+pub const MATE_BOUND: Score = MATE - MAX_PLY as Score;
+
+// Score for giving checkmate `ply` plies from the root
+pub fn mate_in(ply: usize) -> Score {
+    MATE - ply as Score
+}
+
+// Score for being checkmated `ply` plies from the root
+pub fn mated_in(ply: usize) -> Score {
+    -MATE + ply as Score
+}
+
+pub fn is_mate(score: Score) -> bool {
+    score.abs() >= MATE_BOUND
+}
+
+// Number of moves (not plies) until mate: positive when the side to move
+// is giving mate, negative when it is getting mated
+pub fn moves_to_mate(score: Score) -> Option<i32> {
+    if score >= MATE_BOUND {
+        Some((MATE - score + 1) / 2)
+    } else if score <= -MATE_BOUND {
+        Some(-(MATE + score) / 2)
+    } else {
+        None
+    }
+}
+
+// Mate scores are stored in the transposition table relative to the position
+// rather than the root, because the same position can be reached at any ply
+pub fn to_tt(score: Score, ply: usize) -> Score {
+    if score >= MATE_BOUND {
+        score + ply as Score
+    } else if score <= -MATE_BOUND {
+        score - ply as Score
+    } else {
+        score
+    }
+}
+
+pub fn from_tt(score: Score, ply: usize) -> Score {
+    if score >= MATE_BOUND {
+        score - ply as Score
+    } else if score <= -MATE_BOUND {
+        score + ply as Score
+    } else {
+        score
+    }
+}
+
+// Human readable score, ie. "+1.25" or "#3" (mate in 3) / "#-2" (getting mated in 2)
+pub fn to_string(score: Score) -> String {
+    match moves_to_mate(score) {
+        Some(n) => format!("#{}", n),
+        None => format!("{:+.2}", score as f32 / 100.0),
+    }
+}
@@ -0,0 +1,123 @@
+use chess::{BitBoard, Board, ChessMove, Color, Piece, Square, EMPTY};
+use std::cmp;
+use super::alphabeta::{piece_value, PIECES};
+use super::score::Score;
+
+// Longest possible exchange: every piece on the board capturing on one square
+const MAX_EXCHANGE: usize = 32;
+
+// Static exchange evaluation: the material the side to move wins (or loses if negative)
+// by making this move and then letting both sides keep capturing on the destination
+// square with their least valuable piece for as long as it pays off
+pub fn see(board: &Board, m: ChessMove) -> Score {
+    let source = m.get_source();
+    let dest = m.get_dest();
+    let mut occupied = *board.combined();
+
+    let mut captured = match board.piece_on(dest) {
+        Some(piece) => piece_value(piece),
+        None => 0,
+    };
+    let mut attacker = board.piece_on(source).unwrap_or(Piece::Pawn);
+
+    // En passant: the captured pawn isn't on the destination square
+    if attacker == Piece::Pawn && board.piece_on(dest) == None && source.get_file() != dest.get_file() {
+        captured = piece_value(Piece::Pawn);
+        occupied ^= BitBoard::from_square(Square::make_square(source.get_rank(), dest.get_file()));
+    }
+    if let Some(promotion) = m.get_promotion() {
+        captured += piece_value(promotion) - piece_value(Piece::Pawn);
+        attacker = promotion;
+    }
+
+    exchange(board, source, dest, attacker, captured, occupied)
+}
+
+// Pieces of `color` (other than the king) that the opponent can win material by
+// capturing, whoever's turn it is
+pub fn hanging_pieces(board: &Board, color: Color) -> BitBoard {
+    let mut hanging = EMPTY;
+    let occupied = *board.combined();
+    let targets = *board.color_combined(color) & !*board.pieces(Piece::King);
+
+    for square in targets {
+        if let Some((attacker, from)) = least_valuable_attacker(board, square, occupied, !color) {
+            let captured = board.piece_on(square).map_or(0, piece_value);
+            if exchange(board, from, square, attacker, captured, occupied) > 0 {
+                hanging |= BitBoard::from_square(square);
+            }
+        }
+    }
+    hanging
+}
+
+// Swap list algorithm: gain[d] is the score from the point of view of the side making
+// the d-th capture, assuming the exchange stops right after it. Each side can choose
+// to stop capturing, so the list is then resolved backwards with a negamax
+fn exchange(board: &Board, source: Square, dest: Square, attacker: Piece, captured: Score, occupied: BitBoard) -> Score {
+    let mut gain = [0; MAX_EXCHANGE];
+    let mut occupied = occupied;
+    let mut attacker = attacker;
+    let mut from = BitBoard::from_square(source);
+    let mut side = board.color_on(source).unwrap_or(board.side_to_move());
+    let mut d = 0;
+
+    gain[0] = captured;
+    loop {
+        d += 1;
+        gain[d] = piece_value(attacker) - gain[d - 1];
+
+        // Neither side can come out ahead by carrying on
+        if cmp::max(-gain[d - 1], gain[d]) < 0 || d == MAX_EXCHANGE - 1 { break }
+
+        // Removing the piece that just captured can reveal an x-ray attacker behind it
+        occupied ^= from;
+        side = !side;
+        match least_valuable_attacker(board, dest, occupied, side) {
+            Some((piece, square)) => {
+                attacker = piece;
+                from = BitBoard::from_square(square);
+            }
+            None => break,
+        }
+    }
+
+    while d > 1 {
+        d -= 1;
+        gain[d - 1] = -cmp::max(-gain[d - 1], gain[d]);
+    }
+    gain[0]
+}
+
+// Sliding attacks are recomputed from the remaining pieces every time, which is
+// what picks up the x-ray attackers
+pub fn attackers_to(board: &Board, square: Square, occupied: BitBoard) -> BitBoard {
+    let diagonal = *board.pieces(Piece::Bishop) | *board.pieces(Piece::Queen);
+    let straight = *board.pieces(Piece::Rook) | *board.pieces(Piece::Queen);
+    let white_pawns = *board.pieces(Piece::Pawn) & *board.color_combined(Color::White);
+    let black_pawns = *board.pieces(Piece::Pawn) & *board.color_combined(Color::Black);
+
+    let attackers = chess::get_pawn_attacks(square, Color::Black, white_pawns)
+        | chess::get_pawn_attacks(square, Color::White, black_pawns)
+        | (chess::get_knight_moves(square) & *board.pieces(Piece::Knight))
+        | (chess::get_king_moves(square) & *board.pieces(Piece::King))
+        | (chess::get_bishop_moves(square, occupied) & diagonal)
+        | (chess::get_rook_moves(square, occupied) & straight);
+
+    attackers & occupied
+}
+
+fn least_valuable_attacker(board: &Board, square: Square, occupied: BitBoard, color: Color) -> Option<(Piece, Square)> {
+    let attackers = attackers_to(board, square, occupied) & *board.color_combined(color);
+    if attackers == EMPTY {
+        return None
+    }
+
+    for &piece in PIECES.iter() {
+        let candidates = attackers & *board.pieces(piece);
+        if candidates != EMPTY {
+            return Some((piece, candidates.to_square()))
+        }
+    }
+    None
+}
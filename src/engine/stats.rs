@@ -0,0 +1,124 @@
+use std::fmt;
+use std::time::Duration;
+
+// Counters collected during a search to see where the time goes when tuning it.
+// Every thread keeps its own and they are added together at the end, except for
+// nodes_per_depth which only comes from the main thread
+#[derive(Clone, Default, Debug)]
+pub struct SearchStats {
+    pub nodes: u64,
+    pub qnodes: u64,                    // Nodes in the quiescence search
+    pub nodes_per_depth: Vec<u64>,      // Nodes the main thread needed for each completed iteration
+    pub time: Duration,
+
+    pub beta_cutoffs: u64,
+    pub first_move_cutoffs: u64,        // Cutoffs caused by the first move searched
+
+    pub tt_probes: u64,
+    pub tt_hits: u64,
+    pub tt_cutoffs: u64,                // Probes that returned a score without searching
+
+    pub null_move_prunes: u64,
+    pub reverse_futility_prunes: u64,
+    pub razoring_prunes: u64,
+    pub futility_prunes: u64,           // Moves skipped
+    pub delta_prunes: u64,              // Captures skipped in the quiescence search
+    pub see_prunes: u64,                // Losing captures skipped in the quiescence search
+    pub reductions: u64,                // Moves searched with late move reductions
+    pub reduction_researches: u64,      // Reduced moves that had to be searched again at full depth
+}
+
+impl SearchStats {
+    // Adds the counters of another thread
+    pub fn merge(&mut self, other: &SearchStats) {
+        self.qnodes += other.qnodes;
+        self.beta_cutoffs += other.beta_cutoffs;
+        self.first_move_cutoffs += other.first_move_cutoffs;
+        self.tt_probes += other.tt_probes;
+        self.tt_hits += other.tt_hits;
+        self.tt_cutoffs += other.tt_cutoffs;
+        self.null_move_prunes += other.null_move_prunes;
+        self.reverse_futility_prunes += other.reverse_futility_prunes;
+        self.razoring_prunes += other.razoring_prunes;
+        self.futility_prunes += other.futility_prunes;
+        self.delta_prunes += other.delta_prunes;
+        self.see_prunes += other.see_prunes;
+        self.reductions += other.reductions;
+        self.reduction_researches += other.reduction_researches;
+    }
+
+    // How many times more nodes the last iteration needed than the one before it
+    pub fn branching_factor(&self) -> f64 {
+        let n = self.nodes_per_depth.len();
+        if n < 2 || self.nodes_per_depth[n - 2] == 0 {
+            return 0.0
+        }
+        self.nodes_per_depth[n - 1] as f64 / self.nodes_per_depth[n - 2] as f64
+    }
+
+    pub fn first_move_cutoff_rate(&self) -> f64 {
+        ratio(self.first_move_cutoffs, self.beta_cutoffs)
+    }
+
+    pub fn tt_hit_rate(&self) -> f64 {
+        ratio(self.tt_hits, self.tt_probes)
+    }
+
+    pub fn tt_cutoff_rate(&self) -> f64 {
+        ratio(self.tt_cutoffs, self.tt_probes)
+    }
+
+    pub fn qnode_share(&self) -> f64 {
+        ratio(self.qnodes, self.nodes)
+    }
+
+    // A flat JSON object, written by hand to avoid pulling in a dependency
+    pub fn to_json(&self) -> String {
+        let depths: Vec<String> = self.nodes_per_depth.iter().map(|n| n.to_string()).collect();
+        let fields = [
+            format!("\"nodes\":{}", self.nodes),
+            format!("\"qnodes\":{}", self.qnodes),
+            format!("\"nodes_per_depth\":[{}]", depths.join(",")),
+            format!("\"time_ms\":{}", self.time.as_millis()),
+            format!("\"branching_factor\":{:.3}", self.branching_factor()),
+            format!("\"beta_cutoffs\":{}", self.beta_cutoffs),
+            format!("\"first_move_cutoffs\":{}", self.first_move_cutoffs),
+            format!("\"first_move_cutoff_rate\":{:.3}", self.first_move_cutoff_rate()),
+            format!("\"tt_probes\":{}", self.tt_probes),
+            format!("\"tt_hits\":{}", self.tt_hits),
+            format!("\"tt_cutoffs\":{}", self.tt_cutoffs),
+            format!("\"tt_hit_rate\":{:.3}", self.tt_hit_rate()),
+            format!("\"tt_cutoff_rate\":{:.3}", self.tt_cutoff_rate()),
+            format!("\"qnode_share\":{:.3}", self.qnode_share()),
+            format!("\"null_move_prunes\":{}", self.null_move_prunes),
+            format!("\"reverse_futility_prunes\":{}", self.reverse_futility_prunes),
+            format!("\"razoring_prunes\":{}", self.razoring_prunes),
+            format!("\"futility_prunes\":{}", self.futility_prunes),
+            format!("\"delta_prunes\":{}", self.delta_prunes),
+            format!("\"see_prunes\":{}", self.see_prunes),
+            format!("\"reductions\":{}", self.reductions),
+            format!("\"reduction_researches\":{}", self.reduction_researches),
+        ];
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+fn ratio(part: u64, total: u64) -> f64 {
+    if total == 0 { 0.0 } else { part as f64 / total as f64 }
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let depths: Vec<String> = self.nodes_per_depth.iter().map(|n| n.to_string()).collect();
+        writeln!(f, "nodes {} ({:.1}% quiescence) in {:.2?}", self.nodes, self.qnode_share() * 100.0, self.time)?;
+        writeln!(f, "nodes per depth {}", depths.join(" "))?;
+        writeln!(f, "branching factor {:.2}", self.branching_factor())?;
+        writeln!(f, "beta cutoffs {} ({:.1}% on the first move)", self.beta_cutoffs, self.first_move_cutoff_rate() * 100.0)?;
+        writeln!(f, "tt probes {} hits {:.1}% cutoffs {:.1}%", self.tt_probes,
+                 self.tt_hit_rate() * 100.0, self.tt_cutoff_rate() * 100.0)?;
+        writeln!(f, "pruned: null move {} reverse futility {} razoring {} futility {} delta {} see {}",
+                 self.null_move_prunes, self.reverse_futility_prunes, self.razoring_prunes,
+                 self.futility_prunes, self.delta_prunes, self.see_prunes)?;
+        write!(f, "reductions {} re-searched {}", self.reductions, self.reduction_researches)
+    }
+}
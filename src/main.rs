@@ -2,15 +2,22 @@ use ggez;
 use nalgebra;
 use std::env;
 use std::path;
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use ggez::conf;
 use ggez::{Context, GameResult};
-use ggez::event::{self, MouseButton};
+use ggez::event::{self, KeyCode, KeyMods, MouseButton};
 use ggez::graphics::{self, Color, MeshBuilder, DrawParam};
 
 use chess::{Board, Square, ChessMove, BoardStatus, EMPTY};
 
+use engine::limits::SearchLimits;
+use engine::progress::{SearchProgress, SearchResult};
+
 //Import external modules
 mod engine;
 mod tests;
@@ -18,6 +25,9 @@ mod tests;
 use nalgebra as na;
 type Point2 = na::Point2<f32>;
 
+//How long the engine thinks about each move
+const ENGINE_MOVE_TIME: Duration = Duration::from_secs(5);
+
 //Sprites for each piece
 struct Assets {
 	black_pawn: graphics::Image,
@@ -92,7 +102,23 @@ struct MainState {
 	mouse_down: bool,
 	assets: Assets,
 	board: Board,
+	history: engine::GameHistory,
 	remember: Remember,
+	engine_move: Option<mpsc::Receiver<SearchResult>>,	//Some while the engine is thinking
+	progress: Option<mpsc::Receiver<SearchProgress>>,
+	stop: Arc<AtomicBool>,							//Tells the engine to move now
+	timer: Instant,
+	searcher: Arc<Mutex<engine::Searcher>>,			//Kept between moves so pondering isn't wasted
+	ponder: Option<Ponder>,							//Some while the engine thinks on the player's time
+}
+
+//The engine searching the position after the reply it expects from the player
+struct Ponder {
+	expected: ChessMove,
+	pondering: Arc<AtomicBool>,		//Cleared on a ponder hit to start the engine's clock
+	stop: Arc<AtomicBool>,
+	engine_move: mpsc::Receiver<SearchResult>,
+	progress: mpsc::Receiver<SearchProgress>,
 }
 
 impl MainState {
@@ -100,6 +126,7 @@ impl MainState {
 		let assets = Assets::new(ctx)?;
 		let remember = Remember::initialize();
 		let board = Board::default();
+		let history = engine::GameHistory::new(&board);
 
         Ok (MainState {
             pos_x: 100.0,
@@ -107,10 +134,91 @@ impl MainState {
 			mouse_down: false,
 			assets: assets,
 			board: board,
+			history: history,
 			remember: remember,
+			engine_move: None,
+			progress: None,
+			stop: Arc::new(AtomicBool::new(false)),
+			timer: Instant::now(),
+			searcher: Arc::new(Mutex::new(engine::new_searcher())),
+			ponder: None,
 		})
 	}
 
+	//Run the engine on its own thread so the window keeps responding while it thinks
+	fn start_engine(&self, board: Board, history: engine::GameHistory, limits: SearchLimits)
+		-> (mpsc::Receiver<SearchResult>, mpsc::Receiver<SearchProgress>, Arc<AtomicBool>) {
+		let (sender, receiver) = mpsc::channel();
+		let (progress_sender, progress_receiver) = mpsc::channel();
+		let mut board = board;
+		let mut history = history;
+		let searcher = self.searcher.clone();
+		let stop = Arc::new(AtomicBool::new(false));
+		let engine_stop = stop.clone();
+		thread::spawn(move || {
+			//Waits for a stopped ponder search to give the searcher back
+			let mut searcher = searcher.lock().unwrap();
+			let result = engine::ai_move(&mut board, &mut history, &limits, &mut searcher, &engine_stop, Some(progress_sender));
+			let _ = sender.send(result);
+		});
+		(receiver, progress_receiver, stop)
+	}
+
+	//Think about the position after the player's expected reply while they think about their move
+	fn start_pondering(&mut self, expected: Option<ChessMove>) {
+		let expected = match expected {
+			Some(m) if self.board.legal(m) => m,
+			_ => return,
+		};
+		let board = self.board.make_move_new(expected);
+		if board.status() != BoardStatus::Ongoing {
+			return
+		}
+
+		let mut history = self.history.clone();
+		history.push(&self.board, expected);
+		let pondering = Arc::new(AtomicBool::new(true));
+		let limits = SearchLimits { ponder: Some(pondering.clone()), ..SearchLimits::move_time(ENGINE_MOVE_TIME) };
+		let (engine_move, progress, stop) = self.start_engine(board, history, limits);
+		self.ponder = Some(Ponder {
+			expected: expected,
+			pondering: pondering,
+			stop: stop,
+			engine_move: engine_move,
+			progress: progress,
+		});
+	}
+
+	//Stop the engine and start over, the engine forgets what it learned last game
+	fn new_game(&mut self) {
+		self.stop.store(true, Ordering::Relaxed);
+		if let Some(ponder) = self.ponder.take() {
+			ponder.stop.store(true, Ordering::Relaxed);
+		}
+		self.engine_move = None;
+		self.progress = None;
+		self.board = Board::default();
+		self.history = engine::GameHistory::new(&self.board);
+		self.remember = Remember::initialize();
+		self.searcher.lock().unwrap().new_game();
+	}
+
+	//On a ponder hit the search carries on as the engine's normal search,
+	//on a miss it is stopped and whatever it found is thrown away
+	fn stop_pondering(&mut self, played: ChessMove) {
+		if let Some(ponder) = self.ponder.take() {
+			if played == ponder.expected {
+				ponder.pondering.store(false, Ordering::Relaxed);
+				self.engine_move = Some(ponder.engine_move);
+				self.progress = Some(ponder.progress);
+				self.stop = ponder.stop;
+				self.timer = Instant::now();
+			} else {
+				ponder.stop.store(true, Ordering::Relaxed);
+			}
+		}
+	}
+
 	//Draw the 8x8 board
 	fn draw_board(&self, ctx: &mut Context) -> GameResult<()>{
 		let (tile_width, tile_height) = self.tile_size(ctx);
@@ -490,11 +598,37 @@ impl ggez::event::EventHandler for MainState {
 			return Ok(())
 		}
 		if self.board.side_to_move() == chess::Color::Black {
-			let timer = Instant::now();
-			let m = engine::ai_move(&mut self.board);
-			println!("Time to calculate move: {:.2?}", timer.elapsed());
-			self.remember.released_square = m.get_source();
-			self.remember.last_pressed_square = m.get_dest();
+			match self.engine_move {
+				None => {
+					let limits = SearchLimits::move_time(ENGINE_MOVE_TIME);
+					let (engine_move, progress, stop) = self.start_engine(self.board, self.history.clone(), limits);
+					self.engine_move = Some(engine_move);
+					self.progress = Some(progress);
+					self.stop = stop;
+					self.timer = Instant::now();
+				}
+				//Print what the engine is thinking and check if it has finished
+				Some(ref receiver) => {
+					if let Some(ref progress) = self.progress {
+						for info in progress.try_iter() {
+							println!("{}", info);
+						}
+					}
+					if let Ok(result) = receiver.try_recv() {
+						println!("Time to calculate move: {:.2?}", self.timer.elapsed());
+						println!("{}", result.stats);
+						self.engine_move = None;
+						//Only happens if the game was already over
+						if let Some(m) = result.best_move {
+							self.history.push(&self.board, m);
+							self.board = self.board.make_move_new(m);
+							self.remember.released_square = m.get_source();
+							self.remember.last_pressed_square = m.get_dest();
+							self.start_pondering(result.ponder_move());
+						}
+					}
+				}
+			}
 		}
 		Ok(())
 	}
@@ -525,12 +659,25 @@ impl ggez::event::EventHandler for MainState {
 			self.pos_y = y;
 		}
 	}
+	//Space makes the engine play the best move it has found so far, N starts a new game
+	fn key_down_event(&mut self, ctx: &mut Context, keycode: KeyCode, _keymods: KeyMods, _repeat: bool){
+		match keycode {
+			KeyCode::Space => self.stop.store(true, Ordering::Relaxed),
+			KeyCode::N => self.new_game(),
+			KeyCode::Escape => event::quit(ctx),
+			_ => (),
+		}
+	}
 	fn mouse_button_down_event(&mut self, ctx: &mut Context, _button: MouseButton, x: f32, y: f32){
 		self.mouse_down = true;
 		self.remember.curr_pressed_square = self.coordinate_to_square(ctx, (x, y));
 	}
 	fn mouse_button_up_event(&mut self, ctx: &mut Context, _button: MouseButton, x: f32, y: f32){
 		self.mouse_down = false;
+		//No moving while the engine is thinking
+		if self.engine_move.is_some() {
+			return
+		}
 		let released;
 		//Check for release outside the window
 		let (tile_width, tile_height) = self.tile_size(ctx);
@@ -555,9 +702,11 @@ impl ggez::event::EventHandler for MainState {
 				if self.board.legal(m) {
 					self.remember.curr_pressed_square = released;	//For highlighting
 					self.remember.display_last_move = true;			//Allow highlights
+					self.history.push(&self.board, m);
 					let mut result = Board::default();
 					self.board.make_move(m, &mut result);
 					self.board = result;
+					self.stop_pondering(m);
 				}
 			}
 		}
@@ -567,6 +716,11 @@ impl ggez::event::EventHandler for MainState {
 fn main() -> GameResult{
 	// Tests
 	//tests::eval_speed_test();
+	//tests::pruning_speed_test();
+	//tests::see_test();
+	//tests::tt_test();
+	//tests::mate_score_test();
+	//tests::fifty_move_mate_test();
 
 	//Add path of sprite folder
 	let sprite_dir = if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
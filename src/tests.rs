@@ -1,6 +1,21 @@
 use chess::{Board, MoveGen, Square, ChessMove, BoardStatus, EMPTY};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 use std::time::Instant;
 
+use crate::engine;
+use crate::engine::limits::SearchLimits;
+use crate::engine::score;
+
+//Positions used to compare search features
+const BENCH_POSITIONS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+];
+
 pub fn eval_speed_test(){
     let board = Board::default();
 
@@ -47,6 +62,112 @@ pub fn eval_speed_test(){
     println!("Method 2, Elapsed time: {:.2?}", timer2.elapsed());
 }
 
+//Search every bench position to the same depth with and without forward pruning
+pub fn pruning_speed_test(){
+    let limits = SearchLimits::depth(7);
+    let no_pruning = engine::SearchConfig {
+        futility_max_depth: 0,
+        reverse_futility_max_depth: 0,
+        razoring_max_depth: 0,
+        ..engine::SearchConfig::default()
+    };
+    let configs = [("Without pruning", no_pruning), ("With pruning", engine::SearchConfig::default())];
+
+    for (name, config) in configs.iter() {
+        let mut nodes = 0;
+        let timer = Instant::now();
+        for fen in BENCH_POSITIONS.iter() {
+            let board = Board::from_str(fen).unwrap();
+            nodes += engine::search_with_config(&board, &limits, config).nodes;
+        }
+        println!("{}, Nodes: {}, Elapsed time: {:.2?}", name, nodes, timer.elapsed());
+    }
+}
+
+//Static exchange evaluation of positions with a known result
+pub fn see_test(){
+    let positions = [
+        //Rook takes a pawn defended by a rook
+        ("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", Square::E1, Square::E5, None, 100),
+        //Knight takes a pawn and gets traded off in a long exchange
+        ("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", Square::D3, Square::E5, None, -220),
+        //Queen takes a pawn defended by a pawn
+        ("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1", Square::D1, Square::D5, None, -800),
+        //The second rook backs up the first one through it (x-ray)
+        ("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", Square::D2, Square::D5, None, 100),
+        //En passant
+        ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", Square::E5, Square::D6, None, 100),
+        //Promotion
+        ("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", Square::B7, Square::B8, Some(chess::Piece::Queen), 800),
+    ];
+
+    for &(fen, source, dest, promotion, expected) in positions.iter() {
+        let board = Board::from_str(fen).unwrap();
+        let m = ChessMove::new(source, dest, promotion);
+        assert_eq!(engine::see::see(&board, m), expected, "SEE of {} in {}", m, fen);
+    }
+
+    //The black knight is attacked by a pawn, the white knight is attacked by a knight but defended by a pawn
+    let board = Board::from_str("4k3/8/2p5/3n4/4PN2/6P1/8/3K4 w - - 0 1").unwrap();
+    assert_eq!(engine::see::hanging_pieces(&board, chess::Color::Black), chess::BitBoard::from_square(Square::D5));
+    assert_eq!(engine::see::hanging_pieces(&board, chess::Color::White), EMPTY);
+    println!("SEE: {} positions passed", positions.len());
+}
+
+//Entries have to come back out of the transposition table exactly as they were stored
+pub fn tt_test(){
+    let tt = engine::TranspositionTable::new(1);
+    let promotion = ChessMove::new(Square::B7, Square::B8, Some(chess::Piece::Knight));
+    let entries = [
+        (1, Some(ChessMove::new(Square::E2, Square::E4, None)), 35, 7, engine::Bound::Exact),
+        (2, Some(promotion), -1234, 255, engine::Bound::Lower),
+        (3, None, -score::INFINITY, 0, engine::Bound::Upper),
+        (4, None, score::to_tt(score::mate_in(9), 4), 1, engine::Bound::Exact),
+    ];
+
+    for &(key, best_move, value, depth, bound) in entries.iter() {
+        tt.store(key, best_move, value, depth, bound);
+        let entry = tt.probe(key).expect("entry was not stored");
+        assert_eq!(entry.best_move, best_move);
+        assert_eq!(entry.score, value);
+        assert_eq!(entry.depth as u32, depth);
+        assert_eq!(entry.bound, bound);
+    }
+    println!("Transposition table: {} entries round trip", entries.len());
+}
+
+//Mate scores are stored in the transposition table relative to the position
+//and have to come back relative to the ply they are probed at
+pub fn mate_score_test(){
+    let cases = [
+        (score::mate_in(9), 4, 4, score::mate_in(9)),
+        (score::mate_in(9), 4, 6, score::mate_in(11)),
+        (score::mated_in(8), 3, 1, score::mated_in(6)),
+        (250, 5, 9, 250),
+    ];
+
+    for &(value, ply, probe_ply, expected) in cases.iter() {
+        assert_eq!(score::from_tt(score::to_tt(value, ply), probe_ply), expected, "{} stored at ply {}", value, ply);
+    }
+    assert_eq!(score::moves_to_mate(score::mate_in(9)), Some(5));
+    assert_eq!(score::moves_to_mate(score::mated_in(8)), Some(-4));
+    println!("Mate scores: {} cases passed", cases.len());
+}
+
+//Checkmate takes precedence over the 50 move rule: the mate lands on the 100th halfmove
+pub fn fifty_move_mate_test(){
+    let board = Board::from_str("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    let mut history = engine::GameHistory::new(&board);
+    history.halfmove_clock = 99;
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut searcher = engine::Searcher::new(engine::SearchConfig::default());
+
+    let result = searcher.search(board, &history, &SearchLimits::depth(4), &stop, None);
+    assert_eq!(result.best_move, Some(ChessMove::new(Square::A1, Square::A8, None)));
+    assert_eq!(result.score, score::mate_in(1));
+    println!("Fifty move mate: {} {}", result.best_move.unwrap(), score::to_string(result.score));
+}
+
 fn get_piece_balance(board: Board, piece: chess::Piece) -> i32 {
     let mut color = board.color_combined(chess::Color::White);
     let pieces = board.pieces(piece);